@@ -0,0 +1,54 @@
+//! A comparison of SeaHash against other popular non-cryptographic hash functions.
+//!
+//! Every function hashes the same buffers, over a range of sizes from small keys to bulk data.
+//! Run with `cargo bench -p seahash-bench-compare` from the `seahash` workspace.
+
+#[macro_use]
+extern crate criterion;
+extern crate seahash;
+extern crate xxhash_rust;
+extern crate fxhash;
+extern crate ahash;
+
+use std::hash::{BuildHasher, Hasher};
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box};
+
+/// The sizes of the hashed buffers (in bytes).
+const SIZES: [usize; 7] = [4, 16, 32, 64, 256, 4096, 1 << 20];
+
+fn comparison(c: &mut Criterion) {
+    // Arbitrary, but not trivially compressible data.
+    let data: Vec<u8> = (0..SIZES[SIZES.len() - 1]).map(|i| (i * 131 + 7) as u8).collect();
+    let ahash_state = ahash::RandomState::with_seeds(1, 2, 3, 4);
+
+    let mut group = c.benchmark_group("comparison");
+    for &size in &SIZES {
+        let buf = &data[..size];
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("seahash", size), buf, |b, buf| {
+            b.iter(|| seahash::hash(black_box(buf)))
+        });
+        group.bench_with_input(BenchmarkId::new("xxh64", size), buf, |b, buf| {
+            b.iter(|| xxhash_rust::xxh64::xxh64(black_box(buf), 0))
+        });
+        group.bench_with_input(BenchmarkId::new("xxh3", size), buf, |b, buf| {
+            b.iter(|| xxhash_rust::xxh3::xxh3_64(black_box(buf)))
+        });
+        group.bench_with_input(BenchmarkId::new("fxhash", size), buf, |b, buf| {
+            b.iter(|| fxhash::hash64(black_box(buf)))
+        });
+        group.bench_with_input(BenchmarkId::new("ahash", size), buf, |b, buf| {
+            b.iter(|| {
+                let mut h = ahash_state.build_hasher();
+                h.write(black_box(buf));
+                h.finish()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, comparison);
+criterion_main!(benches);
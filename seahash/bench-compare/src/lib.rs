@@ -0,0 +1,4 @@
+//! Benchmarks comparing SeaHash against other hash functions.
+//!
+//! This crate is not published: it only exists to keep the benchmark framework and the other hash
+//! functions out of the dependencies of `seahash`. See `benches/comparison.rs`.
@@ -17,3 +17,19 @@ fn gigabyte(b: &mut test::Bencher) {
         x
     })
 }
+
+#[bench]
+fn gigabyte_unaligned(b: &mut test::Bencher) {
+    // Like `gigabyte`, but every buffer has excessive bytes, so the tail is handled every time.
+    b.iter(|| {
+        let mut x = 0;
+        let mut buf = [15; 4099];
+
+        for _ in 0..250000 {
+            x ^= seahash::hash(&buf);
+            buf[0] += buf[0].wrapping_add(1);
+        }
+
+        x
+    })
+}
@@ -0,0 +1,96 @@
+#![feature(test)]
+
+extern crate test;
+extern crate seahash;
+
+use std::hash::{Hash, Hasher};
+
+#[bench]
+fn u64_pairs(b: &mut test::Bencher) {
+    b.iter(|| {
+        let mut x = 0;
+        for i in 0..4096u64 {
+            let mut h = seahash::SeaHasher::new();
+            (test::black_box(i), test::black_box(!i)).hash(&mut h);
+            x ^= h.finish();
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn mixed_integers(b: &mut test::Bencher) {
+    b.iter(|| {
+        let mut h = seahash::SeaHasher::new();
+        for i in 0..4096u32 {
+            h.write_u8(test::black_box(i as u8));
+            h.write_u32(test::black_box(i));
+            h.write_u16(test::black_box(i as u16));
+        }
+
+        h.finish()
+    })
+}
+
+#[bench]
+fn byte_at_a_time(b: &mut test::Bencher) {
+    let buf = [15; 4096];
+
+    b.iter(|| {
+        let mut h = seahash::SeaHasher::new();
+        for &byte in test::black_box(&buf[..]) {
+            h.write_u8(byte);
+        }
+
+        h.finish()
+    })
+}
+
+#[bench]
+fn byte_at_a_time_slices(b: &mut test::Bencher) {
+    let buf = [15; 4096];
+
+    b.iter(|| {
+        let mut h = seahash::SeaHasher::new();
+        for byte in test::black_box(&buf[..]).chunks(1) {
+            h.write(byte);
+        }
+
+        h.finish()
+    })
+}
+
+#[bench]
+fn large_write(b: &mut test::Bencher) {
+    let buf = vec![15; 1 << 20];
+
+    b.bytes = buf.len() as u64;
+    b.iter(|| {
+        let mut h = seahash::SeaHasher::new();
+        h.write(test::black_box(&buf));
+        h.finish()
+    })
+}
+
+#[bench]
+fn large_write_unaligned(b: &mut test::Bencher) {
+    let buf = vec![15; 1 << 20];
+
+    b.bytes = buf.len() as u64;
+    b.iter(|| {
+        let mut h = seahash::SeaHasher::new();
+        // Leave the cursor in the middle of the state vector, so the blocks are not aligned to it.
+        h.write(test::black_box(&buf[..13]));
+        h.write(test::black_box(&buf[13..]));
+        h.finish()
+    })
+}
+
+#[bench]
+fn large_hash_seeded(b: &mut test::Bencher) {
+    let buf = vec![15; 1 << 20];
+
+    b.bytes = buf.len() as u64;
+    b.iter(|| seahash::hash_seeded(test::black_box(&buf), 0))
+}
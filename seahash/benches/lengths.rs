@@ -0,0 +1,179 @@
+#![feature(test)]
+
+extern crate test;
+extern crate seahash;
+
+/// Generate pseudorandom lengths in `[0, max)`.
+fn lengths(max: usize) -> Vec<usize> {
+    let mut x = 0x5eed_u64;
+    (0..4096).map(|_| {
+        x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (x >> 33) as usize % max
+    }).collect()
+}
+
+#[bench]
+fn random_lengths_64(b: &mut test::Bencher) {
+    let lengths = lengths(64);
+    let buf = [15; 64];
+
+    b.iter(|| {
+        let mut x = 0;
+        for &n in &lengths {
+            x ^= seahash::hash(test::black_box(&buf[..n]));
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn random_lengths_128(b: &mut test::Bencher) {
+    let lengths = lengths(128);
+    let buf = [15; 128];
+
+    b.iter(|| {
+        let mut x = 0;
+        for &n in &lengths {
+            x ^= seahash::hash(test::black_box(&buf[..n]));
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn random_lengths_16(b: &mut test::Bencher) {
+    let lengths = lengths(17);
+    let buf = [15; 16];
+
+    b.iter(|| {
+        let mut x = 0;
+        for &n in &lengths {
+            x ^= seahash::hash_seeded(test::black_box(&buf[..n]), 1);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn random_lengths_16_small(b: &mut test::Bencher) {
+    let lengths = lengths(17);
+    let buf = [15; 16];
+
+    b.iter(|| {
+        let mut x = 0;
+        for &n in &lengths {
+            x ^= seahash::hash_small(test::black_box(&buf[..n]), 1);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn fixed_length_4(b: &mut test::Bencher) {
+    let buf = [15; 4];
+
+    b.iter(|| {
+        let mut x = 0;
+        for seed in 0..4096 {
+            x ^= seahash::hash_seeded(test::black_box(&buf), seed);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn random_lengths_8_32(b: &mut test::Bencher) {
+    let lengths: Vec<usize> = lengths(24).into_iter().map(|n| n + 8).collect();
+    let buf = [15; 32];
+
+    b.iter(|| {
+        let mut x = 0;
+        for &n in &lengths {
+            x ^= seahash::hash(test::black_box(&buf[..n]));
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn fixed_length_20(b: &mut test::Bencher) {
+    let buf = [15; 20];
+
+    b.iter(|| {
+        let mut x = 0;
+        for seed in 0..4096 {
+            x ^= seahash::hash_seeded(test::black_box(&buf), seed);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn fixed_length_256(b: &mut test::Bencher) {
+    let buf = [15; 256];
+
+    b.iter(|| {
+        let mut x = 0;
+        for seed in 0..4096 {
+            x ^= seahash::hash_seeded(test::black_box(&buf), seed);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn fixed_length_16(b: &mut test::Bencher) {
+    let buf = [15; 16];
+
+    b.iter(|| {
+        let mut x = 0;
+        for seed in 0..4096 {
+            x ^= seahash::hash_seeded(test::black_box(&buf), seed);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn fixed_length_32(b: &mut test::Bencher) {
+    let buf = [15; 32];
+
+    b.iter(|| {
+        let mut x = 0;
+        for seed in 0..4096 {
+            x ^= seahash::hash_seeded(test::black_box(&buf), seed);
+        }
+
+        x
+    })
+}
+
+#[bench]
+fn ring_4096(b: &mut test::Bencher) {
+    // A 4 KiB ring buffer which wraps in the middle of a word.
+    let buf = [15; 4096];
+    let (second, first) = buf.split_at(1003);
+
+    b.iter(|| {
+        seahash::hash_ring(test::black_box(first), test::black_box(second), 1)
+    })
+}
+
+#[bench]
+fn contiguous_4096(b: &mut test::Bencher) {
+    // The same bytes as `ring_4096`, but contiguous.
+    let buf = [15; 4096];
+
+    b.iter(|| {
+        seahash::hash_seeded(test::black_box(&buf), 1)
+    })
+}
@@ -0,0 +1,32 @@
+#![feature(test)]
+
+extern crate test;
+extern crate seahash;
+
+const SEEDS: [u64; 4] = [1, 2, 3, 4];
+
+#[bench]
+fn multi_4_seeds(b: &mut test::Bencher) {
+    let buf = [15; 4096];
+
+    b.iter(|| {
+        let mut out = [0; 4];
+        seahash::hash_multi(test::black_box(&buf), &SEEDS, &mut out);
+
+        out
+    })
+}
+
+#[bench]
+fn separate_4_seeds(b: &mut test::Bencher) {
+    let buf = [15; 4096];
+
+    b.iter(|| {
+        let mut out = [0; 4];
+        for (h, &seed) in out.iter_mut().zip(SEEDS.iter()) {
+            *h = seahash::hash_seeded(test::black_box(&buf), seed);
+        }
+
+        out
+    })
+}
@@ -0,0 +1,58 @@
+//! A bare-metal build of SeaHash.
+//!
+//! This crate is not published: it only exists to check that `seahash` works without `std` and
+//! without `alloc`, which the test suite of `seahash` cannot do, as the tests always link `std`.
+//! Being `no_std`, this crate only has `core` to work with, and it depends on `seahash` with the
+//! default features disabled. Build it for a target which ships neither `std` nor an allocator,
+//! e.g.:
+//!
+//! ```text
+//! rustup target add thumbv6m-none-eabi
+//! cargo build -p seahash-no-std-check --target thumbv6m-none-eabi
+//! ```
+//!
+//! The same check for `seahash` alone is `cargo build --no-default-features --target
+//! thumbv6m-none-eabi`. `cargo test -p seahash-no-std-check` runs `stream_matches()` on the host.
+
+#![no_std]
+
+extern crate seahash;
+
+use core::hash::Hasher;
+
+use seahash::SeaHasher;
+
+/// Stream some bytes through `SeaHasher` in uneven writes, and check the digest against the one of
+/// `hash_seeded()`.
+///
+/// The buffer and the hasher both live on the stack.
+pub fn stream_matches(seed: u64) -> bool {
+    let mut buf = [0; 300];
+    for (i, x) in buf.iter_mut().enumerate() {
+        *x = (i as u8).wrapping_mul(31);
+    }
+
+    let mut hasher = SeaHasher::with_seed(seed);
+    for chunk in buf.chunks(7) {
+        hasher.write(chunk);
+    }
+    hasher.write_u8(1);
+
+    let mut expected = [0; 301];
+    expected[..300].copy_from_slice(&buf);
+    expected[300] = 1;
+
+    hasher.finish() == seahash::hash_seeded(&expected, seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream() {
+        for seed in 0..16 {
+            assert!(stream_matches(seed));
+        }
+    }
+}
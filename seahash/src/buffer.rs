@@ -1,14 +1,25 @@
 //! A highly optimized version of SeaHash.
 
-use core::slice;
+#![deny(clippy::arithmetic_side_effects)]
 
-use diffuse;
+use core::{cmp, ptr, slice};
+use core::mem::MaybeUninit;
+
+use {diffuse, diffuse_fast, finalize, finalize_high};
+#[cfg(feature = "debug-crosscheck")]
+use reference;
+use consts::{DEFAULT_SEED, INIT_B, INIT_C, INIT_D, BOOST_TWEAK};
 
 /// Read a buffer smaller than 8 bytes into an integer in little-endian.
 ///
-/// This assumes that `buf.len() < 8`. If this is not satisfied, the behavior is unspecified.
-#[inline(always)]
+/// This assumes that `buf.len() < 8`. If this is not satisfied, the behavior is unspecified (and
+/// debug builds panic at the caller).
+#[cfg_attr(not(feature = "min-size"), inline(always))]
+#[cfg_attr(feature = "min-size", inline)]
+#[track_caller]
 fn read_int(buf: &[u8]) -> u64 {
+    debug_assert!(buf.len() < 8, "The buffer must be shorter than 8 bytes.");
+
     // Because we want to make sure that it is register allocated, we fetch this into a variable.
     // It will likely make no difference anyway, though.
     let ptr = buf.as_ptr();
@@ -20,34 +31,34 @@ fn read_int(buf: &[u8]) -> u64 {
             // u8.
             1 => *ptr as u64,
             // u16.
-            2 => (*(ptr as *const u16)).to_le() as u64,
+            2 => ptr::read_unaligned(ptr as *const u16).to_le() as u64,
             // u16 + u8.
             3 => {
-                let a = (*(ptr as *const u16)).to_le() as u64;
+                let a = ptr::read_unaligned(ptr as *const u16).to_le() as u64;
                 let b = *ptr.offset(2) as u64;
 
                 a | (b << 16)
             },
             // u32.
-            4 => (*(ptr as *const u32)).to_le() as u64,
+            4 => ptr::read_unaligned(ptr as *const u32).to_le() as u64,
             // u32 + u8.
             5 => {
-                let a = (*(ptr as *const u32)).to_le() as u64;
+                let a = ptr::read_unaligned(ptr as *const u32).to_le() as u64;
                 let b = *ptr.offset(4) as u64;
 
                 a | (b << 32)
             },
             // u32 + u16.
             6 => {
-                let a = (*(ptr as *const u32)).to_le() as u64;
-                let b = (*(ptr.offset(4) as *const u16)).to_le() as u64;
+                let a = ptr::read_unaligned(ptr as *const u32).to_le() as u64;
+                let b = ptr::read_unaligned(ptr.offset(4) as *const u16).to_le() as u64;
 
                 a | (b << 32)
             },
             // u32 + u16 + u8.
             7 => {
-                let a = (*(ptr as *const u32)).to_le() as u64;
-                let b = (*(ptr.offset(4) as *const u16)).to_le() as u64;
+                let a = ptr::read_unaligned(ptr as *const u32).to_le() as u64;
+                let b = ptr::read_unaligned(ptr.offset(4) as *const u16).to_le() as u64;
                 let c = *ptr.offset(6) as u64;
 
                 a | (b << 32) | (c << 48)
@@ -58,17 +69,252 @@ fn read_int(buf: &[u8]) -> u64 {
 }
 
 /// Read a little-endian 64-bit integer from some buffer.
-#[inline(always)]
+#[cfg_attr(not(feature = "min-size"), inline(always))]
+#[cfg_attr(feature = "min-size", inline)]
 unsafe fn read_u64(ptr: *const u8) -> u64 {
     #[cfg(target_pointer_width = "32")]
     {
-        (*(ptr as *const u32)).to_le() as u64 | ((*(ptr as *const u32)).to_le() as u64) << 32
+        let low = ptr::read_unaligned(ptr as *const u32).to_le() as u64;
+        let high = ptr::read_unaligned(ptr.offset(4) as *const u32).to_le() as u64;
+
+        low | high << 32
     }
 
-    #[cfg(target_pointer_width = "64")]
+    #[cfg(all(target_pointer_width = "64", not(target_arch = "riscv64")))]
     {
-        (*(ptr as *const u64)).to_le()
+        ptr::read_unaligned(ptr as *const u64).to_le()
+    }
+
+    // Many RISC-V cores trap on misaligned loads and emulate them in firmware, so the compiler
+    // splits loads which may be misaligned into byte loads. Checking the alignment allows a single
+    // full-width load in the (common) aligned case.
+    #[cfg(target_arch = "riscv64")]
+    {
+        if ptr as usize & 7 == 0 {
+            ptr::read(ptr as *const u64).to_le()
+        } else {
+            u64::from_le_bytes(ptr::read(ptr as *const [u8; 8]))
+        }
+    }
+}
+
+/// Write the excessive bytes (less than 32) into the state vector with a reduced number of
+/// branches.
+///
+/// Rather than branching on the number of excessive bytes, this copies them into a zero-padded
+/// 32-byte block and diffuses all four states, and then uses masks to only keep the updates of the
+/// states which the excessive bytes actually reach. This avoids mispredictions when the length of
+/// the input (modulo 32) is unpredictable, but it is slower when it is predictable, since it
+/// always does four diffusions. As such, it is only used with the `branchless-tail` feature.
+///
+/// Note that this doesn't use explicit SIMD: the diffusion consists of 64-bit multiplications,
+/// which most vector extensions lack, so the work is better spent on the four independent scalar
+/// pipelines.
+///
+/// This assumes that `excessive < 32` and that `ptr` is valid for reads of `excessive` bytes.
+#[inline(always)]
+unsafe fn tail_masked<F>(state: [u64; 4], ptr: *const u8, excessive: usize, diffuse: &F) -> [u64; 4]
+    where F: Fn(u64) -> u64 {
+    // Load the excessive bytes, padding with zeros.
+    let mut block = [0; 32];
+    ptr::copy_nonoverlapping(ptr, block.as_mut_ptr(), excessive);
+    let block = block.as_ptr();
+
+    // The number of (possibly partial) words in the block.
+    let words = excessive.div_ceil(8);
+    // Make a mask which is all ones if the word is present, and zero otherwise.
+    let mask = |n: usize| 0u64.wrapping_sub((words > n) as u64);
+
+    // Diffuse every state and select the new value by the masks.
+    [
+        state[0] ^ ((diffuse(state[0] ^ read_u64(block)) ^ state[0]) & mask(0)),
+        state[1] ^ ((diffuse(state[1] ^ read_u64(block.offset(8))) ^ state[1]) & mask(1)),
+        state[2] ^ ((diffuse(state[2] ^ read_u64(block.offset(16))) ^ state[2]) & mask(2)),
+        state[3] ^ ((diffuse(state[3] ^ read_u64(block.offset(24))) ^ state[3]) & mask(3)),
+    ]
+}
+
+/// The length from which the excessive bytes are handled out of line.
+///
+/// For long buffers, the tail is a small, rare part of the work, so it is moved to a cold function
+/// (see `tail_blocks_cold`) to keep the main loop and its surroundings tight. Short buffers consist
+/// mostly of the tail, so it is inlined for them.
+const COLD_TAIL_LEN: usize = 256;
+
+/// Write the excessive bytes (less than 32) into the state vector.
+///
+/// This dispatches on the number of full words (`excessive / 8`) and then on the lane of the
+/// partial word, rather than on every length class, so both matches are dense over `0..4` and
+/// compile to jump tables instead of chains of range comparisons.
+///
+/// This assumes that `excessive < 32` and that `ptr` is valid for reads of `excessive` bytes.
+#[inline(always)]
+unsafe fn tail_blocks<F>(state: [u64; 4], ptr: *const u8, excessive: usize, diffuse: &F) -> [u64; 4]
+    where F: Fn(u64) -> u64 {
+    let [mut a, mut b, mut c, mut d] = state;
+    // The number of full words.
+    let words = excessive >> 3;
+
+    match words {
+        0 => {},
+        1 => {
+            a = diffuse(a ^ read_u64(ptr));
+        },
+        2 => {
+            a = diffuse(a ^ read_u64(ptr));
+            b = diffuse(b ^ read_u64(ptr.offset(8)));
+        },
+        _ => {
+            a = diffuse(a ^ read_u64(ptr));
+            b = diffuse(b ^ read_u64(ptr.offset(8)));
+            c = diffuse(c ^ read_u64(ptr.offset(16)));
+        },
+    }
+
+    // Write the last excessive bytes (<8 bytes) to the lane after the full words.
+    let rest = excessive & 7;
+    if rest != 0 {
+        let x = read_int(slice::from_raw_parts(ptr.add(excessive.wrapping_sub(rest)), rest));
+        match words {
+            0 => a = diffuse(a ^ x),
+            1 => b = diffuse(b ^ x),
+            2 => c = diffuse(c ^ x),
+            _ => d = diffuse(d ^ x),
+        }
+    }
+
+    [a, b, c, d]
+}
+
+/// `tail_blocks`, out of line and marked as unlikely to be called.
+///
+/// This is the same as `tail_blocks`, but it is kept out of the hot code of long buffers (see
+/// `COLD_TAIL_LEN`). The output is the same.
+#[cold]
+#[inline(never)]
+unsafe fn tail_blocks_cold<F>(state: [u64; 4], ptr: *const u8, excessive: usize, diffuse: &F) -> [u64; 4]
+    where F: Fn(u64) -> u64 {
+    tail_blocks(state, ptr, excessive, diffuse)
+}
+
+/// Write pairs of 32-byte blocks into the state vector.
+///
+/// This is the main loop unrolled by a factor of two, i.e. it does eight diffusions (two per
+/// state) per iteration. It gives the same result as going through the blocks one by one, but on
+/// some CPUs the longer sequence of independent operations schedules better, while on others the
+/// bigger loop body is a loss. As such, it is only used with the `unroll8` feature.
+///
+/// This assumes that `ptr` is valid for reads of `64 * pairs` bytes.
+#[inline(always)]
+unsafe fn blocks_unrolled8<F>(state: [u64; 4], mut ptr: *const u8, pairs: usize, diffuse: &F) -> [u64; 4]
+    where F: Fn(u64) -> u64 {
+    let [mut a, mut b, mut c, mut d] = state;
+
+    for _ in 0..pairs {
+        a = diffuse(a ^ read_u64(ptr));
+        b = diffuse(b ^ read_u64(ptr.offset(8)));
+        c = diffuse(c ^ read_u64(ptr.offset(16)));
+        d = diffuse(d ^ read_u64(ptr.offset(24)));
+
+        a = diffuse(a ^ read_u64(ptr.offset(32)));
+        b = diffuse(b ^ read_u64(ptr.offset(40)));
+        c = diffuse(c ^ read_u64(ptr.offset(48)));
+        d = diffuse(d ^ read_u64(ptr.offset(56)));
+
+        ptr = ptr.offset(64);
+    }
+
+    [a, b, c, d]
+}
+
+/// Write some buffer into an 8-slot state, as an 8-lane engine would lay it out.
+///
+/// Word `i` goes to slot `i % 8`, so the slots `j` and `j + 4` both belong to lane `j`. Since the
+/// lanes are chains (every write diffuses the previous value of the lane), the slot `j + 4` is
+/// computed from the slot `j` and vice versa, rather than from its own previous value. Both slots
+/// start out with the initial value of the lane. Returns the slots and the number of words.
+///
+/// Together with `fold_lanes8()`, this is a model of an 8-lane backend: it is exactly the
+/// canonical 4-lane result (see the `lanes8` test), which shows that the two slots of a lane can
+/// never be processed in parallel.
+#[cfg(test)]
+pub(crate) fn write_lanes8(state: [u64; 4], buf: &[u8]) -> ([u64; 8], usize) {
+    let [a, b, c, d] = state;
+    let mut slots = [a, b, c, d, a, b, c, d];
+
+    let mut words = 0usize;
+    for (i, word) in buf.chunks(8).enumerate() {
+        let mut bytes = [0; 8];
+        bytes[..word.len()].copy_from_slice(word);
+
+        let k = i % 8;
+        slots[k] = diffuse(slots[k.wrapping_add(4) % 8] ^ u64::from_le_bytes(bytes));
+        words = words.wrapping_add(1);
+    }
+
+    (slots, words)
+}
+
+/// Fold an 8-slot state (see `write_lanes8()`) back into the 4 lanes.
+///
+/// Lane `j` gets the words `j, j + 4, j + 8, ...`, which alternate between its two slots, so the
+/// lane is the slot which was written last: slot `j` after an odd number of words, and slot
+/// `j + 4` after an even, nonzero number of them. Without any words, both hold the initial value.
+#[cfg(test)]
+pub(crate) fn fold_lanes8(slots: [u64; 8], words: usize) -> [u64; 4] {
+    let mut lanes = [0; 4];
+    for (j, lane) in lanes.iter_mut().enumerate() {
+        // The number of words written to lane `j`.
+        let count = words.wrapping_add(3).wrapping_sub(j) / 4;
+        *lane = if count > 0 && count & 1 == 0 { slots[j.wrapping_add(4)] } else { slots[j] };
+    }
+
+    lanes
+}
+
+/// Fold a buffer of 8 to 31 bytes into the state.
+///
+/// These buffers never enter the main loop, so they would go straight to the `match` on the
+/// excessive bytes. Instead, this reads the full words with at most two (length) comparisons, and
+/// reads the partial word as the last 8 bytes of the buffer shifted down, which avoids the
+/// byte-wise `read_int()`. The result is the same as the one of the general path.
+///
+/// This assumes that `8 <= buf.len() < 32`.
+#[inline(always)]
+unsafe fn hash_medium<F>(state: [u64; 4], buf: &[u8], diffuse: &F) -> u64
+    where F: Fn(u64) -> u64 {
+    let [mut a, mut b, mut c, mut d] = state;
+    let ptr = buf.as_ptr();
+    let len = buf.len();
+
+    // The number of bytes in the partial word.
+    let excessive = len & 7;
+    // Since the buffer is at least 8 bytes long, we can read the last 8 bytes and shift out the
+    // ones belonging to the previous word to get the partial word. If there are no excessive
+    // bytes, this is not used (and the mask keeps the shift in range).
+    let shift = 64usize.wrapping_sub(excessive.wrapping_mul(8)) & 63;
+    let last = read_u64(ptr.add(len.wrapping_sub(8))) >> shift;
+
+    a = diffuse(a ^ read_u64(ptr));
+    if len < 16 {
+        if excessive != 0 {
+            b = diffuse(b ^ last);
+        }
+    } else if len < 24 {
+        b = diffuse(b ^ read_u64(ptr.offset(8)));
+        if excessive != 0 {
+            c = diffuse(c ^ last);
+        }
+    } else {
+        b = diffuse(b ^ read_u64(ptr.offset(8)));
+        c = diffuse(c ^ read_u64(ptr.offset(16)));
+        if excessive != 0 {
+            d = diffuse(d ^ last);
+        }
     }
+
+    // Fold the state and XOR the length, like `hash_raw()`.
+    a ^ b ^ c ^ d ^ len as u64
 }
 
 /// Hash some buffer.
@@ -88,7 +334,7 @@ unsafe fn read_u64(ptr: *const u8) -> u64 {
 ///
 /// and more.
 pub fn hash(buf: &[u8]) -> u64 {
-    hash_seeded(buf, 0x16f11fe89b0d677c)
+    hash_seeded(buf, DEFAULT_SEED)
 }
 
 /// Hash some buffer according to a chosen seed.
@@ -99,21 +345,523 @@ pub fn hash(buf: &[u8]) -> u64 {
 /// cryptoanalysis.
 ///
 /// The seed is expected to be chosen from an uniform distribution.
+///
+/// In terms of the building blocks, this starts with the lanes `[seed, b, c, d]` (with the
+/// constants of the specification), updates lane `i % 4` with `mix_block()` for the `i`'th 8-byte
+/// block of the buffer (in little-endian, the last one padded with zeros), and gives
+/// `finalize(lanes, buf.len())`.
+///
+/// The implementation is chosen entirely at compile time (by the features of the crate), without
+/// any runtime CPU feature detection, so there is no dispatch overhead per call.
 pub fn hash_seeded(buf: &[u8], seed: u64) -> u64 {
+    let digest = diffuse(hash_raw(buf, seed));
+    #[cfg(feature = "debug-crosscheck")]
+    crosscheck(buf, seed, digest);
+
+    digest
+}
+
+/// Check a digest of `hash_seeded()` against the reference implementation.
+///
+/// This panics if they differ, catching regressions of the optimized paths in the test suites of
+/// dependents. It only checks in debug builds (the reference is not even run otherwise), and it
+/// is kept out of line, as it is much slower than the hash itself.
+#[cfg(feature = "debug-crosscheck")]
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn crosscheck(buf: &[u8], seed: u64, digest: u64) {
+    debug_assert_eq!(digest, reference::hash_seeded(buf, seed),
+                     "The digest diverges from the reference implementation.");
+}
+
+/// Hash anything which can be viewed as a byte slice.
+///
+/// This is `hash()` taking any `AsRef<[u8]>`, such as `Vec<u8>`, `Box<[u8]>`, `Rc<[u8]>`,
+/// `Arc<[u8]>`, `String`, `str` or byte arrays, which is convenient in generic code (concrete
+/// references are usually coerced to `&[u8]` already). The digest is the one of the underlying
+/// bytes.
+pub fn hash_ref<T: AsRef<[u8]> + ?Sized>(buf: &T) -> u64 {
+    hash(buf.as_ref())
+}
+
+/// Hash some buffer according to a chosen seed, distinguishing inputs which differ only in
+/// trailing zero bytes.
+///
+/// This is `hash_seeded()` under a name advertising the guarantee. The last block is padded with
+/// zeros, so `[1, 2, 3]` and `[1, 2, 3, 0]` fill the lanes identically, but the length of the
+/// input is XOR'd into the folded state before the final diffusion, and as the diffusion function
+/// is bijective, inputs of distinct lengths with identical lanes never collide. That covers zeros
+/// appended within the last partial word. Otherwise (e.g. when appending 8 or more zeros, which
+/// writes more words and thus changes the lanes), collisions are as likely as for any other pair
+/// of inputs, i.e. about 2^-64. The guarantee only holds for the full 64-bit digest with the
+/// canonical diffusion function, so neither for truncated digests (e.g. `hash32()`) nor for
+/// `hash_with_mixer()` with a mixer which is not bijective.
+pub fn hash_zero_sensitive(buf: &[u8], seed: u64) -> u64 {
+    hash_seeded(buf, seed)
+}
+
+/// Whether inputs differing only in trailing zero bytes which fill the lanes identically (i.e.
+/// zeros within the last partial word) always have distinct digests.
+///
+/// This is always `true` (see `hash_zero_sensitive()` for the exact guarantee), so e.g. code
+/// generators can assert the property rather than assume it.
+pub const fn trailing_zero_sensitive() -> bool {
+    true
+}
+
+/// Hash some buffer, but skip the final diffusion.
+///
+/// This returns the folded state (the XOR of the four states and the length) right before it is
+/// passed through the diffusion function, i.e. `diffuse(hash_raw(buf, seed))` equals
+/// `hash_seeded(buf, seed)`.
+///
+/// This is useful when chaining hashes (e.g. feeding the output into the seed of another stage),
+/// which will mix the value anyway. Note that the raw value is **not** well-distributed on its own
+/// (e.g. changing the length alone only changes the lower bits), so it should not be used directly
+/// as a hash.
+#[inline]
+pub fn hash_raw(buf: &[u8], seed: u64) -> u64 {
+    hash_raw_with(buf, seed, diffuse)
+}
+
+/// Hash some buffer with the fast, single-round diffusion.
+///
+/// This is a **distinct** hash function, which is _not_ compatible with `hash_seeded()`. It follows
+/// the same construction, but every block is mixed in with `diffuse_fast()`, which is only one of
+/// the two multiply-shift rounds of the diffusion function. The final diffusion is still the full
+/// one. See the reference implementation for the specification.
+///
+/// This trades statistical quality for speed: a single round has notably worse avalanche behavior
+/// (flipping the high bits of a block leaves most of the lane unaffected), and the quality
+/// guarantees of SeaHash do not carry over. Only use it in hot loops where slightly worse
+/// distribution is acceptable, and never for persistent digests expected to match `hash()`.
+pub fn hash_fast(buf: &[u8], seed: u64) -> u64 {
+    diffuse(hash_raw_with(buf, seed, diffuse_fast))
+}
+
+/// Hash a small buffer (at most 16 bytes) according to a chosen seed.
+///
+/// This gives the same result as `hash_seeded()`, but it is tuned for inputs of at most 16 bytes
+/// (e.g. integer keys or short identifiers) whose length is unpredictable: after reading the
+/// (at most two) words, both are diffused unconditionally, and masks select the updates of the
+/// lanes which the input actually reaches. As such, the branches on the length are confined to
+/// reading the input (the choice between the byte-wise and the word-wise read, and the byte-wise
+/// `read_int()` itself). Longer buffers are a bug (checked in debug builds), but they fall back
+/// to `hash_seeded()` in release builds, so the result is always correct.
+#[track_caller]
+pub fn hash_small(buf: &[u8], seed: u64) -> u64 {
+    debug_assert!(buf.len() <= 16, "The buffer must be at most 16 bytes long.");
+    hash_small_with_fallback(buf, seed)
+}
+
+/// `hash_small()` without the debug assertion, such that the fallback is tested in debug builds.
+#[inline(always)]
+fn hash_small_with_fallback(buf: &[u8], seed: u64) -> u64 {
+    if buf.len() > 16 {
+        return hash_seeded(buf, seed);
+    }
+
+    let len = buf.len();
+    let (x, y) = if len < 8 {
+        (read_int(buf), 0)
+    } else {
+        // Read the second word as the last 8 bytes shifted down, like `hash_medium()`. If there is
+        // no second word, it is masked out below anyway.
+        unsafe {
+            let ptr = buf.as_ptr();
+            let shift = 128usize.wrapping_sub(len.wrapping_mul(8)) & 63;
+            (read_u64(ptr), read_u64(ptr.add(len.wrapping_sub(8))) >> shift)
+        }
+    };
+
+    // All ones if the lane receives a word, and zero otherwise.
+    let mask_a = 0u64.wrapping_sub((len > 0) as u64);
+    let mask_b = 0u64.wrapping_sub((len > 8) as u64);
+    let a = (diffuse(seed ^ x) & mask_a) | (seed & !mask_a);
+    let b = (diffuse(INIT_B ^ y) & mask_b) | (INIT_B & !mask_b);
+
+    finalize([a, b, INIT_C, INIT_D], len as u64)
+}
+
+/// Hash some buffer with every block tweaked by its position.
+///
+/// This is a **distinct** hash function, which is _not_ compatible with `hash_seeded()`: every
+/// block is XORed with a multiple of its (1-based) index before it is mixed into its lane, i.e.
+/// the lane update is `diffuse(lane ^ block ^ i * t)` for some odd constant `t`. See the reference
+/// implementation for the specification.
+///
+/// This makes every lane update depend on the position of the block, in addition to its lane, so
+/// e.g. equal blocks at different positions of the same lane enter it differently. It costs an
+/// addition and an XOR per block. Note that `hash_seeded()` already gives uniformly distributed
+/// low bits for sequential short keys (as tested), so this is only worth it if measurements on
+/// the actual keys show an improvement.
+pub fn hash_boosted(buf: &[u8], seed: u64) -> u64 {
+    let mut a = seed;
+    let mut b = INIT_B;
+    let mut c = INIT_C;
+    let mut d = INIT_D;
+    // The tweak of the current block, i.e. its index times the tweak constant.
+    let mut tweak = 0u64;
+
+    let mut blocks = buf.chunks_exact(32);
+    for block in &mut blocks {
+        let ptr = block.as_ptr();
+        unsafe {
+            tweak = tweak.wrapping_add(BOOST_TWEAK);
+            a = diffuse(a ^ read_u64(ptr) ^ tweak);
+            tweak = tweak.wrapping_add(BOOST_TWEAK);
+            b = diffuse(b ^ read_u64(ptr.offset(8)) ^ tweak);
+            tweak = tweak.wrapping_add(BOOST_TWEAK);
+            c = diffuse(c ^ read_u64(ptr.offset(16)) ^ tweak);
+            tweak = tweak.wrapping_add(BOOST_TWEAK);
+            d = diffuse(d ^ read_u64(ptr.offset(24)) ^ tweak);
+        }
+    }
+
+    // Handle the excessive (possibly partial) words, which go to the lanes in order.
+    let mut state = [a, b, c, d];
+    for (lane, word) in state.iter_mut().zip(blocks.remainder().chunks(8)) {
+        tweak = tweak.wrapping_add(BOOST_TWEAK);
+        let x = if word.len() == 8 {
+            unsafe { read_u64(word.as_ptr()) }
+        } else {
+            read_int(word)
+        };
+        *lane = diffuse(*lane ^ x ^ tweak);
+    }
+
+    diffuse(state[0] ^ state[1] ^ state[2] ^ state[3] ^ buf.len() as u64)
+}
+
+/// Hash some buffer according to several seeds in one pass.
+///
+/// This sets `out[i]` to `hash_seeded(buf, seeds[i])` for every seed, e.g. for the hash functions
+/// of a sketch. Only the first lane of the state depends on the seed, so the other three lanes are
+/// shared between the seeds: for `k` seeds, every 32-byte block takes `k + 3` diffusions rather
+/// than `4k`, and the buffer is read only once.
+///
+/// # Panics
+///
+/// This panics if `out` is shorter than `seeds`.
+pub fn hash_multi(buf: &[u8], seeds: &[u64], out: &mut [u64]) {
+    // The first lane of every seed is kept in `out`.
+    let out = &mut out[..seeds.len()];
+    out.copy_from_slice(seeds);
+    let mut b = INIT_B;
+    let mut c = INIT_C;
+    let mut d = INIT_D;
+
+    let mut blocks = buf.chunks_exact(32);
+    for block in &mut blocks {
+        let ptr = block.as_ptr();
+        unsafe {
+            let x = read_u64(ptr);
+            for a in out.iter_mut() {
+                *a = diffuse(*a ^ x);
+            }
+
+            b = diffuse(b ^ read_u64(ptr.offset(8)));
+            c = diffuse(c ^ read_u64(ptr.offset(16)));
+            d = diffuse(d ^ read_u64(ptr.offset(24)));
+        }
+    }
+
+    // Handle the excessive (possibly partial) words, which go to the lanes in order.
+    for (lane, word) in blocks.remainder().chunks(8).enumerate() {
+        let x = if word.len() == 8 {
+            unsafe { read_u64(word.as_ptr()) }
+        } else {
+            read_int(word)
+        };
+
+        match lane {
+            0 => for a in out.iter_mut() {
+                *a = diffuse(*a ^ x);
+            },
+            1 => b = diffuse(b ^ x),
+            2 => c = diffuse(c ^ x),
+            _ => d = diffuse(d ^ x),
+        }
+    }
+
+    let shared = b ^ c ^ d ^ buf.len() as u64;
+    for a in out.iter_mut() {
+        *a = diffuse(*a ^ shared);
+    }
+}
+
+/// Hash the bytes at some raw pointer according to a chosen seed.
+///
+/// This is `hash_seeded()` for memory which is not (yet) available as a `&[u8]`, e.g. in an
+/// allocator. It simply forms the slice and delegates to `hash_seeded()`.
+///
+/// # Safety
+///
+/// The same as for `slice::from_raw_parts(ptr, len)`. In particular:
+///
+/// - `ptr` must be non-null and valid for reads of `len` bytes (even if `len` is zero, in which
+///   case a dangling, non-null pointer suffices), within a single allocated object.
+/// - All of the `len` bytes must be initialized.
+/// - The memory must not be mutated (e.g. through an aliasing pointer or by another thread) for
+///   the duration of the call.
+/// - `len` must be no larger than `isize::MAX`.
+pub unsafe fn hash_raw_parts(ptr: *const u8, len: usize, seed: u64) -> u64 {
+    hash_seeded(slice::from_raw_parts(ptr, len), seed)
+}
+
+/// Hash the initialized prefix of a partially initialized buffer according to a chosen seed.
+///
+/// This is `hash_seeded()` over the first `init_len` bytes of `buf`, e.g. the filled part of a
+/// receive buffer, without first converting it to a `&[u8]`.
+///
+/// This panics if `init_len` is greater than the length of `buf`.
+///
+/// # Safety
+///
+/// The first `init_len` bytes of `buf` must be initialized. The rest are never read.
+pub unsafe fn hash_maybe_uninit(buf: &[MaybeUninit<u8>], init_len: usize, seed: u64) -> u64 {
+    let init = &buf[..init_len];
+    // `MaybeUninit<u8>` has the same layout as `u8`, and the caller guarantees initialization.
+    hash_raw_parts(init.as_ptr() as *const u8, init.len(), seed)
+}
+
+/// Hash an array of 64-bit integers according to a chosen seed.
+///
+/// The result is the same as `hash_seeded()` of the little-endian encoding of the elements, on
+/// every platform: the elements are mixed into the lanes as the words they encode to, rather than
+/// being read from memory, so big-endian hosts give the same digests as little-endian ones. As
+/// the length is known at compile time, there is no tail handling. This is e.g. useful for
+/// coordinate vectors (`f64`s can be hashed by their `to_bits()`).
+pub fn hash_u64_array<const N: usize>(arr: &[u64; N], seed: u64) -> u64 {
+    let mut state = [seed, INIT_B, INIT_C, INIT_D];
+    for (i, &x) in arr.iter().enumerate() {
+        state[i % 4] = diffuse(state[i % 4] ^ x);
+    }
+
+    finalize(state, (N as u64).wrapping_mul(8))
+}
+
+/// Hash some buffer into a 128-bit digest according to a chosen seed.
+///
+/// The lanes evolve exactly like in `hash_seeded()`, and the lower 64 bits of the digest are the
+/// 64-bit digest. The upper 64 bits fold the same lanes in a different, non-linear way (see the
+/// specification in the `reference` module), which lowers the probability of collisions for
+/// non-adversarial inputs to about 2^-128.
+pub fn hash128(buf: &[u8], seed: u64) -> u128 {
+    hash_multi_width(buf, seed).1
+}
+
+/// Hash some buffer into a 64-bit and a 128-bit digest at once, according to a chosen seed.
+///
+/// This returns `(hash_seeded(buf, seed), hash128(buf, seed))`, but goes over the buffer only
+/// once, as both digests are folded from the same lanes. Note that the 64-bit digest is then also
+/// the lower word of the 128-bit one.
+pub fn hash_multi_width(buf: &[u8], seed: u64) -> (u64, u128) {
+    let (state, tail) = write_blocks([seed, INIT_B, INIT_C, INIT_D], buf);
+    // The tail is shorter than 32 bytes.
+    let state = unsafe { tail_blocks(state, tail.as_ptr(), tail.len(), &diffuse) };
+
+    let low = finalize(state, buf.len() as u64);
+    let high = finalize_high(state, buf.len() as u64);
+
+    (low, (high as u128) << 64 | low as u128)
+}
+
+/// Hash a buffer of `n` zero bytes according to a chosen seed, without the buffer.
+///
+/// The result is the same as `hash_seeded()` of `n` zero bytes (e.g. a sparse region of a file),
+/// but no memory is read: a zero block leaves the lane unchanged before the diffusion, so every
+/// lane is just its initial value diffused once per block it receives. Note that this still
+/// takes one diffusion per 8 bytes (the iterated diffusion function has no shortcut), so it is
+/// O(n), but without any memory traffic.
+pub fn hash_zeros(n: usize, seed: u64) -> u64 {
+    let mut state = [seed, INIT_B, INIT_C, INIT_D];
+
+    // The number of blocks, the last one possibly partial (which is padded with zeros anyway).
+    let blocks = n.div_ceil(8);
+    for (lane, x) in state.iter_mut().enumerate() {
+        // The blocks go to the lanes round-robin, so the first `blocks % 4` lanes get one more.
+        let rounds = (blocks / 4).wrapping_add((lane < blocks % 4) as usize);
+        for _ in 0..rounds {
+            *x = diffuse(*x);
+        }
+    }
+
+    finalize(state, n as u64)
+}
+
+/// Hash two buffers in one interleaved pass according to a chosen seed, and compare the digests.
+///
+/// This returns `(hash_seeded(a, seed), hash_seeded(b, seed), equal)`, where `equal` tells whether
+/// the digests are equal. The 32-byte blocks of the buffers are processed in lockstep, which keeps
+/// both streams going through the cache together when e.g. scanning corresponding chunks of two
+/// files. The buffers can have different lengths.
+pub fn hash_compare(a: &[u8], b: &[u8], seed: u64) -> (u64, u64, bool) {
+    let mut state_a = [seed, INIT_B, INIT_C, INIT_D];
+    let mut state_b = state_a;
+
+    let (blocks_a, blocks_b) = (a.chunks_exact(32), b.chunks_exact(32));
+    // The number of blocks which both buffers have.
+    let common = cmp::min(blocks_a.len(), blocks_b.len());
+    for (block_a, block_b) in blocks_a.zip(blocks_b) {
+        state_a = write_blocks(state_a, block_a).0;
+        state_b = write_blocks(state_b, block_b).0;
+    }
+
+    // Finish each buffer on its own.
+    let finish = |state, buf: &[u8]| {
+        let (state, tail) = write_blocks(state, &buf[common.wrapping_mul(32)..]);
+        // The tail is shorter than 32 bytes.
+        let state = unsafe { tail_blocks(state, tail.as_ptr(), tail.len(), &diffuse) };
+
+        finalize(state, buf.len() as u64)
+    };
+    let (hash_a, hash_b) = (finish(state_a, a), finish(state_b, b));
+
+    (hash_a, hash_b, hash_a == hash_b)
+}
+
+/// Hash the two segments of a wrapped-around ring buffer according to a chosen seed.
+///
+/// This hashes `first` followed by `second` as one logical buffer, i.e. the result is the same as
+/// `hash_seeded()` of their concatenation, but without copying them. Both segments go through the
+/// main loop in place; only the 32-byte block straddling the wrap point is gathered into a
+/// temporary block.
+pub fn hash_ring(first: &[u8], second: &[u8], seed: u64) -> u64 {
+    let state = [seed, INIT_B, INIT_C, INIT_D];
+    let (state, rest) = write_blocks(state, first);
+
+    // Complete the block straddling the wrap point with the start of `second`. If there is not
+    // enough for a whole block, the gathered bytes are the tail.
+    let mut block = [0; 32];
+    // The rest is shorter than a block.
+    let room = 32usize.wrapping_sub(rest.len());
+    let (state, tail) = if second.len() < room {
+        let end = rest.len().wrapping_add(second.len());
+        block[..rest.len()].copy_from_slice(rest);
+        block[rest.len()..end].copy_from_slice(second);
+
+        (state, &block[..end])
+    } else {
+        let (head, second) = second.split_at(room);
+        block[..rest.len()].copy_from_slice(rest);
+        block[rest.len()..].copy_from_slice(head);
+
+        let (state, _) = write_blocks(state, &block);
+        write_blocks(state, second)
+    };
+
+    // The tail is shorter than 32 bytes.
+    let state = unsafe { tail_blocks(state, tail.as_ptr(), tail.len(), &diffuse) };
+
+    finalize(state, (first.len() as u64).wrapping_add(second.len() as u64))
+}
+
+/// Write the whole 32-byte blocks of some buffer into the state vector.
+///
+/// Returns the new state and the excessive bytes (less than 32) after the blocks.
+#[inline(always)]
+pub(crate) fn write_blocks(state: [u64; 4], buf: &[u8]) -> ([u64; 4], &[u8]) {
+    let [mut a, mut b, mut c, mut d] = state;
+
+    let mut blocks = buf.chunks_exact(32);
+    for block in &mut blocks {
+        let ptr = block.as_ptr();
+        unsafe {
+            a = diffuse(a ^ read_u64(ptr));
+            b = diffuse(b ^ read_u64(ptr.offset(8)));
+            c = diffuse(c ^ read_u64(ptr.offset(16)));
+            d = diffuse(d ^ read_u64(ptr.offset(24)));
+        }
+    }
+
+    ([a, b, c, d], blocks.remainder())
+}
+
+/// Fold some buffer into the state with a compact loop.
+///
+/// This goes through the buffer word by word, indexing the state vector rather than keeping the
+/// components in separate registers, and without any special-casing of the length. The result is
+/// the same as the one of the general path, but it is considerably smaller (and slower). As such,
+/// it is only used with the `min-size` feature.
+#[inline(never)]
+fn hash_compact<F: Fn(u64) -> u64>(mut state: [u64; 4], buf: &[u8], diffuse: &F) -> u64 {
+    for (i, word) in buf.chunks(8).enumerate() {
+        let x = if word.len() == 8 {
+            unsafe { read_u64(word.as_ptr()) }
+        } else {
+            read_int(word)
+        };
+        state[i % 4] = diffuse(state[i % 4] ^ x);
+    }
+
+    state[0] ^ state[1] ^ state[2] ^ state[3] ^ buf.len() as u64
+}
+
+/// Fold some buffer into the state given some diffusion function.
+///
+/// This is the optimized implementation of `hash_raw()`, which is generic over the diffusion
+/// function, so the variants of SeaHash can share it.
+#[cfg_attr(not(feature = "min-size"), inline(always))]
+#[cfg_attr(feature = "min-size", inline)]
+pub(crate) fn hash_raw_with<F: Fn(u64) -> u64>(buf: &[u8], seed: u64, diffuse: F) -> u64 {
     unsafe {
         // We use 4 different registers to store seperate hash states, because this allows us to update
         // them seperately, and consequently exploiting ILP to update the states in parallel.
         let mut a = seed;
-        let mut b = 0xb480a793d8e6c86c;
-        let mut c = 0x6fe2e5aaf078ebc9;
-        let mut d = 0x14f994a4c5259381;
+        let mut b = INIT_B;
+        let mut c = INIT_C;
+        let mut d = INIT_D;
+
+        if cfg!(feature = "min-size") {
+            return hash_compact([a, b, c, d], buf, &diffuse);
+        }
+
+        // 16 and 32 bytes are very common key sizes (e.g. UUIDs and 256-bit digests), so they get
+        // straight-line code without any of the general machinery. This doesn't use SIMD: the
+        // loads are plain 64-bit loads (which the compiler may merge), and the diffusions consist
+        // of 64-bit multiplications, which are better spent on the independent scalar pipelines.
+        if buf.len() == 16 {
+            let ptr = buf.as_ptr();
+            a = diffuse(a ^ read_u64(ptr));
+            b = diffuse(b ^ read_u64(ptr.offset(8)));
+
+            return a ^ b ^ c ^ d ^ 16;
+        }
+        if buf.len() == 32 {
+            let ptr = buf.as_ptr();
+            a = diffuse(a ^ read_u64(ptr));
+            b = diffuse(b ^ read_u64(ptr.offset(8)));
+            c = diffuse(c ^ read_u64(ptr.offset(16)));
+            d = diffuse(d ^ read_u64(ptr.offset(24)));
+
+            return a ^ b ^ c ^ d ^ 32;
+        }
+
+        // Buffers of 8 to 31 bytes (e.g. most keys of hash tables) never enter the main loop,
+        // so we handle them without the general machinery.
+        if buf.len() >= 8 && buf.len() < 32 {
+            return hash_medium([a, b, c, d], buf, &diffuse);
+        }
 
         // The pointer to the current bytes.
         let mut ptr = buf.as_ptr();
-        /// The end of the "main segment", i.e. the biggest buffer s.t. the length is divisible by
-        /// 32.
+        // The end of the "main segment", i.e. the biggest buffer s.t. the length is divisible by
+        // 32.
         let end_ptr = buf.as_ptr().offset(buf.len() as isize & !0x1F) as usize;
 
+        if cfg!(feature = "unroll8") {
+            // Go through the pairs of blocks first, leaving at most one block for the loop below.
+            let pairs = buf.len() >> 6;
+            let state = blocks_unrolled8([a, b, c, d], ptr, pairs, &diffuse);
+            a = state[0];
+            b = state[1];
+            c = state[2];
+            d = state[3];
+            ptr = ptr.add(pairs.wrapping_mul(64));
+        }
+
         while end_ptr > ptr as usize {
             // Read and diffuse the next 4 64-bit little-endian integers from their bytes. Note
             // that we on purpose not use `^=` and co., because it aliases the lvalue, making it
@@ -145,87 +893,42 @@ pub fn hash_seeded(buf: &[u8], seed: u64) -> u64 {
         let mut excessive = end_ptr;
         // Calculate the number of excessive bytes. These are bytes that could not be handled in
         // the loop above.
-        excessive = buf.len() as usize + buf.as_ptr() as usize - excessive as usize;
+        excessive = buf.len().wrapping_add(buf.as_ptr() as usize).wrapping_sub(excessive);
         // Handle the excessive bytes.
-        match excessive {
-            0 => {},
-            1...7 => {
-                // Write the last excessive bytes (<8 bytes).
-                a = diffuse(a ^ read_int(slice::from_raw_parts(ptr as *const u8, excessive)));
-            },
-            8 => {
-                // Update `a`.
-                a = diffuse(a ^ read_u64(ptr));
-            },
-            9...15 => {
-                // Update `a`.
-                a = diffuse(a ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-
-                // Write the last excessive bytes (<8 bytes).
-                excessive = excessive - 8;
-                b = diffuse(b ^ read_int(slice::from_raw_parts(ptr as *const u8, excessive)));
-            },
-            16 => {
-                // Update `a`.
-                a = diffuse(a ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-                // Update `b`.
-                b = diffuse(b ^ read_u64(ptr));
-            },
-            17...23 => {
-                // Update `a`.
-                a = diffuse(a ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-                // Update `b`.
-                b = diffuse(b ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-
-                // Write the last excessive bytes (<8 bytes).
-                excessive = excessive - 16;
-                c = diffuse(c ^ read_int(slice::from_raw_parts(ptr as *const u8, excessive)));
-            },
-            24 => {
-                // Update `a`.
-                a = diffuse(a ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-                // Update `b`.
-                b = diffuse(b ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-                // Update `c`.
-                c = diffuse(c ^ read_u64(ptr));
-            },
-            _ => {
-                // Update `a`.
-                a = diffuse(a ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-                // Update `b`.
-                b = diffuse(b ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-                // Update `c`.
-                c = diffuse(c ^ read_u64(ptr));
-                ptr = ptr.offset(8);
-
-                // Write the last excessive bytes (<8 bytes).
-                excessive = excessive - 24;
-                d = diffuse(d ^ read_int(slice::from_raw_parts(ptr as *const u8, excessive)));
-            }
+        if cfg!(feature = "branchless-tail") {
+            let state = tail_masked([a, b, c, d], ptr, excessive, &diffuse);
+            a = state[0];
+            b = state[1];
+            c = state[2];
+            d = state[3];
+        } else if buf.len() < COLD_TAIL_LEN {
+            let state = tail_blocks([a, b, c, d], ptr, excessive, &diffuse);
+            a = state[0];
+            b = state[1];
+            c = state[2];
+            d = state[3];
+        } else if excessive != 0 {
+            // Keep the tail of long buffers out of line.
+            let state = tail_blocks_cold([a, b, c, d], ptr, excessive, &diffuse);
+            a = state[0];
+            b = state[1];
+            c = state[2];
+            d = state[3];
         }
 
         // XOR the states together. Even though XOR is commutative, it doesn't matter, because the
         // state vector's initial components are mutually distinct, and thus swapping even and odd
         // chunks will affect the result, because it is sensitive to the initial condition.
-        a = a ^ b;
-        c = c ^ d;
-        a = a ^ c;
+        a ^= b;
+        c ^= d;
+        a ^= c;
         // XOR the number of written bytes in order to make the excessive bytes zero-sensitive
         // (without this, two excessive zeros would be equivalent to three excessive zeros). This
         // is know as length padding.
-        a = a ^ buf.len() as u64;
-
-        // We diffuse to make the excessive bytes discrete (i.e. small changes shouldn't give small
-        // changes in the output).
-        diffuse(a)
+        //
+        // The caller diffuses this to make the excessive bytes discrete (i.e. small changes
+        // shouldn't give small changes in the output).
+        a ^ buf.len() as u64
     }
 }
 
@@ -243,10 +946,21 @@ mod tests {
         assert_eq!(hash_seeded(a, !0), reference::hash_seeded(a, !0));
     }
 
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "shorter than 8 bytes")]
+    fn read_int_full_word() {
+        read_int(&[0; 8]);
+    }
+
     #[test]
     fn zero() {
+        // Miri is far too slow for every length, but the shorter ones still cover every tail
+        // after the main loop.
+        let max = if cfg!(miri) { 128 } else { 4096 };
+
         let arr = [0; 4096];
-        for n in 0..4096 {
+        for n in 0..max {
             hash_match(&arr[0..n]);
         }
     }
@@ -254,8 +968,8 @@ mod tests {
     #[test]
     fn seq() {
         let mut buf = [0; 4096];
-        for i in 0..4096 {
-            buf[i] = i as u8;
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = i as u8;
         }
         hash_match(&buf);
     }
@@ -264,12 +978,12 @@ mod tests {
     #[test]
     fn position_depedent() {
         let mut buf1 = [0; 4098];
-        for i in 0..4098 {
-            buf1[i] = i as u8;
+        for (i, x) in buf1.iter_mut().enumerate() {
+            *x = i as u8;
         }
         let mut buf2 = [0; 4098];
-        for i in 0..4098 {
-            buf2[i] = i as u8 ^ 1;
+        for (i, x) in buf2.iter_mut().enumerate() {
+            *x = i as u8 ^ 1;
         }
 
         assert!(hash(&buf1) != hash(&buf2));
@@ -290,6 +1004,35 @@ mod tests {
         assert_ne!(hash(&[0, 0, 0]), hash(&[0, 0, 0, 0, 0]));
     }
 
+    #[test]
+    fn zero_sensitive_guarantee() {
+        assert!(trailing_zero_sensitive());
+
+        assert_ne!(hash_zero_sensitive(&[1, 2, 3], 7), hash_zero_sensitive(&[1, 2, 3, 0], 7));
+        assert_ne!(hash_zero_sensitive(&[1, 2, 3, 4], 7), hash_zero_sensitive(&[1, 0, 2, 3, 4], 7));
+        assert_ne!(hash_zero_sensitive(&[1, 2, 3, 4], 7), hash_zero_sensitive(&[0, 1, 2, 3, 4], 7));
+        assert_ne!(hash_zero_sensitive(&[], 7), hash_zero_sensitive(&[0], 7));
+
+        // The guarantee: zeros completing the last partial word leave the lanes as they are.
+        let mut word = [0; 16];
+        word[..11].copy_from_slice(&[0xAB; 11]);
+        for n in 12..=16 {
+            // `hash_raw()` is the folded lanes XOR'd with the length.
+            assert_eq!(hash_raw(&word[..11], 7) ^ 11, hash_raw(&word[..n], 7) ^ n as u64);
+            assert_ne!(hash_zero_sensitive(&word[..11], 7), hash_zero_sensitive(&word[..n], 7));
+        }
+
+        // More zeros change the lanes, so this is not guaranteed, but any collision is as
+        // unlikely as for other inputs.
+        let buf = [0xAB; 40];
+        let mut padded = [0; 80];
+        padded[..40].copy_from_slice(&buf);
+        for n in 1..=40 {
+            assert_ne!(hash_zero_sensitive(&buf, 7), hash_zero_sensitive(&padded[..40 + n], 7));
+        }
+        assert_eq!(hash_zero_sensitive(&buf, 7), hash_seeded(&buf, 7));
+    }
+
     #[test]
     fn not_equal() {
         assert_ne!(hash(b"to be or not to be "), hash(b"to be or not to be"));
@@ -304,4 +1047,460 @@ mod tests {
         assert_ne!(hash(b"iiiiiiiijkjke"), hash(b"iiiiiiiijkjk"));
         assert_ne!(hash(b"ab"), hash(b"bb"));
     }
+
+    #[test]
+    fn medium() {
+        let mut buf = [0; 40];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 29 + 7) as u8;
+        }
+
+        // Test every length of the medium path (and its boundaries), at every alignment.
+        for start in 0..8 {
+            for n in 7..33 {
+                hash_match(&buf[start..start + n]);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_sizes() {
+        let mut buf = [0; 32];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 29 + 3) as u8;
+        }
+
+        for &seed in &[0, 1, 500, !0, 0x16f11fe89b0d677c] {
+            assert_eq!(hash_seeded(&buf[..16], seed), reference::hash_seeded(&buf[..16], seed));
+            assert_eq!(hash_seeded(&buf, seed), reference::hash_seeded(&buf, seed));
+            assert_eq!(hash_fast(&buf[..16], seed), reference::hash_fast(&buf[..16], seed));
+            assert_eq!(hash_fast(&buf, seed), reference::hash_fast(&buf, seed));
+        }
+    }
+
+    #[cfg(feature = "debug-crosscheck")]
+    #[test]
+    fn crosschecked() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..buf.len() {
+            crosscheck(&buf[..n], 42, hash_seeded(&buf[..n], 42));
+        }
+    }
+
+    #[cfg(all(feature = "debug-crosscheck", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "diverges from the reference")]
+    fn crosscheck_divergence() {
+        crosscheck(b"to be or not to be", 42, hash_seeded(b"to be or not to be", 42) ^ 1);
+    }
+
+    #[test]
+    fn tail_lengths() {
+        let mut buf = [0; 128];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 53 + 11) as u8;
+        }
+
+        // Every number of full words and every partial word of the tail, after zero to three
+        // blocks.
+        for n in 0..buf.len() {
+            hash_match(&buf[..n]);
+            assert_eq!(hash_fast(&buf[..n], 7), reference::hash_fast(&buf[..n], 7));
+        }
+    }
+
+    #[test]
+    fn cold_tail() {
+        let mut buf = [0; COLD_TAIL_LEN + 64];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 37 + 5) as u8;
+        }
+
+        // Every tail length, just below and above the threshold.
+        for n in COLD_TAIL_LEN - 32..buf.len() {
+            hash_match(&buf[..n]);
+        }
+    }
+
+    #[test]
+    fn masked_tail() {
+        let mut buf = [0; 32];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 11 + 1) as u8;
+        }
+
+        for n in 0..32 {
+            let init = [500, INIT_B, INIT_C, INIT_D];
+            let state = unsafe { tail_masked(init, buf.as_ptr(), n, &diffuse) };
+            let folded = state[0] ^ state[1] ^ state[2] ^ state[3] ^ n as u64;
+
+            assert_eq!(diffuse(folded), reference::hash_seeded(&buf[..n], 500));
+        }
+    }
+
+    #[test]
+    fn unrolled8() {
+        let mut buf = [0; 256];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7 + 5) as u8;
+        }
+
+        for pairs in 0..4 {
+            let init = [500, INIT_B, INIT_C, INIT_D];
+            let state = unsafe { blocks_unrolled8(init, buf.as_ptr(), pairs, &diffuse) };
+
+            // Write the same words one by one.
+            let mut expected = init;
+            for (i, word) in buf[..64 * pairs].chunks(8).enumerate() {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(word);
+                expected[i % 4] = diffuse(expected[i % 4] ^ u64::from_le_bytes(bytes));
+            }
+
+            assert_eq!(state, expected);
+        }
+    }
+
+    #[test]
+    fn lanes8() {
+        use finalize;
+
+        let mut buf = [0; 256];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 13 + 9) as u8;
+        }
+
+        // Every length up to 256 bytes, i.e. multiples of 64 bytes as well as arbitrary tails.
+        for n in 0..buf.len() {
+            let init = [500, INIT_B, INIT_C, INIT_D];
+            let (slots, words) = write_lanes8(init, &buf[..n]);
+            assert_eq!(words, n.div_ceil(8));
+
+            assert_eq!(finalize(fold_lanes8(slots, words), n as u64), hash_seeded(&buf[..n], 500));
+        }
+    }
+
+    #[test]
+    fn compact() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 13 + 7) as u8;
+        }
+
+        for n in 0..300 {
+            let init = [500, INIT_B, INIT_C, INIT_D];
+            let raw = hash_compact(init, &buf[..n], &diffuse);
+
+            assert_eq!(diffuse(raw), reference::hash_seeded(&buf[..n], 500));
+        }
+    }
+
+    #[test]
+    fn fast() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 5 + 3) as u8;
+        }
+
+        for n in 0..300 {
+            assert_eq!(hash_fast(&buf[..n], 1), reference::hash_fast(&buf[..n], 1));
+            assert_eq!(hash_fast(&buf[..n], !0), reference::hash_fast(&buf[..n], !0));
+        }
+
+        assert_eq!(hash_fast(b"", 0), 2509897130690332299);
+        assert_eq!(hash_fast(b"to be or not to be", 0), 12992478214828644410);
+        assert_eq!(hash_fast(b"love is a wonderful terrible thing", 42), 9929961457784558382);
+        assert_ne!(hash_fast(b"to be or not to be", 0), hash_seeded(b"to be or not to be", 0));
+    }
+
+    #[test]
+    fn boosted() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 5 + 3) as u8;
+        }
+
+        for n in 0..300 {
+            assert_eq!(hash_boosted(&buf[..n], 1), reference::hash_boosted(&buf[..n], 1));
+            assert_eq!(hash_boosted(&buf[..n], !0), reference::hash_boosted(&buf[..n], !0));
+        }
+
+        assert_eq!(hash_boosted(b"", 0), 2509897130690332299);
+        assert_eq!(hash_boosted(b"to be or not to be", 0), 17894569484418998628);
+        assert_eq!(hash_boosted(b"love is a wonderful terrible thing", 42), 13048937867563188480);
+        // Without blocks, there is nothing to tweak.
+        assert_eq!(hash_boosted(b"", 0), hash_seeded(b"", 0));
+        assert_ne!(hash_boosted(b"abcd", 0), hash_seeded(b"abcd", 0));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn boosted_sequential_keys() {
+        const KEYS: u32 = 1 << 16;
+
+        for &f in &[hash_seeded as fn(&[u8], u64) -> u64, hash_boosted] {
+            // Count the low 8 bits of the digests of sequential 4-byte keys.
+            let mut counts = [0u32; 256];
+            for key in 0..KEYS {
+                counts[(f(&key.to_le_bytes(), 0) & 0xFF) as usize] += 1;
+            }
+
+            // Pearson's chi-squared test against the uniform distribution, allowing six standard
+            // deviations (like `bucket_uniform` of the `output` module).
+            let expected = KEYS as f64 / 256.0;
+            let chi2: f64 = counts.iter()
+                .map(|&c| (c as f64 - expected) * (c as f64 - expected) / expected)
+                .sum();
+            assert!(chi2 <= 255.0 + 6.0 * 510f64.sqrt(), "chi2 = {}", chi2);
+        }
+    }
+
+    #[test]
+    fn multi() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 5 + 1) as u8;
+        }
+        let seeds = [0, 1, 500, !0, 0x16f11fe89b0d677c];
+
+        for n in 0..300 {
+            let mut out = [0; 6];
+            hash_multi(&buf[..n], &seeds, &mut out);
+
+            for (&seed, &h) in seeds.iter().zip(out.iter()) {
+                assert_eq!(h, hash_seeded(&buf[..n], seed));
+            }
+            // The rest is left alone.
+            assert_eq!(out[5], 0);
+        }
+
+        hash_multi(b"abc", &[], &mut []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multi_short_output() {
+        hash_multi(b"abc", &[1, 2], &mut [0]);
+    }
+
+    #[test]
+    fn raw() {
+        let mut buf = [0; 128];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 3) as u8;
+        }
+
+        for n in 0..128 {
+            assert_eq!(diffuse(hash_raw(&buf[..n], 1)), hash_seeded(&buf[..n], 1));
+            assert_eq!(diffuse(hash_raw(&buf[..n], !0)), hash_seeded(&buf[..n], !0));
+        }
+    }
+
+    #[test]
+    fn raw_parts() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..buf.len() {
+            let slice = &buf[..n];
+            unsafe {
+                assert_eq!(hash_raw_parts(slice.as_ptr(), slice.len(), 1), hash_seeded(slice, 1));
+            }
+        }
+
+        let empty: &[u8] = &[];
+        unsafe {
+            assert_eq!(hash_raw_parts(empty.as_ptr(), 0, 1), hash_seeded(empty, 1));
+        }
+    }
+
+    #[test]
+    fn hash_ref_slices() {
+        let data = b"to be or not to be";
+
+        assert_eq!(hash_ref(data), hash(data));
+        assert_eq!(hash_ref(&data[..]), hash(data));
+        assert_eq!(hash_ref("to be or not to be"), hash(data));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn hash_ref_containers() {
+        use alloc::boxed::Box;
+        use alloc::rc::Rc;
+        use alloc::string::String;
+        use alloc::sync::Arc;
+        use alloc::vec::Vec;
+
+        let data = b"to be or not to be";
+        let vec: Vec<u8> = data.to_vec();
+        let boxed: Box<[u8]> = data.to_vec().into_boxed_slice();
+        let rc: Rc<[u8]> = Rc::from(&data[..]);
+        let arc: Arc<[u8]> = Arc::from(&data[..]);
+        let string = String::from("to be or not to be");
+
+        assert_eq!(hash_ref(&vec), hash(data));
+        assert_eq!(hash_ref(&boxed), hash(data));
+        assert_eq!(hash_ref(&rc), hash(data));
+        assert_eq!(hash_ref(&arc), hash(data));
+        assert_eq!(hash_ref(&string), hash(data));
+    }
+
+    #[test]
+    fn multi_width() {
+        let mut buf = [0; 200];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7) as u8;
+        }
+
+        for n in 0..buf.len() {
+            let (narrow, wide) = hash_multi_width(&buf[..n], 42);
+            assert_eq!(narrow, hash_seeded(&buf[..n], 42));
+            assert_eq!(wide, hash128(&buf[..n], 42));
+            assert_eq!(wide, reference::hash128(&buf[..n], 42));
+            assert_eq!(wide as u64, narrow);
+            assert_ne!((wide >> 64) as u64, narrow);
+        }
+        assert_ne!(hash128(b"to be or not to be", 1), hash128(b"to be or not to be", 2));
+    }
+
+    #[test]
+    fn zeros() {
+        let buf = [0; 5000];
+
+        for n in 0..300 {
+            assert_eq!(hash_zeros(n, 42), hash_seeded(&buf[..n], 42));
+        }
+        for &n in &[1000, 1001, 4095, 4096, 4097, 4999, 5000] {
+            assert_eq!(hash_zeros(n, 42), hash_seeded(&buf[..n], 42));
+            assert_eq!(hash_zeros(n, 7), hash_seeded(&buf[..n], 7));
+        }
+        assert_eq!(hash_zeros(18, DEFAULT_SEED), hash(&[0; 18]));
+    }
+
+    #[test]
+    fn compare() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7) as u8;
+        }
+        let mut other = buf;
+        other[150] ^= 1;
+
+        for &(a, b) in &[(&buf[..], &buf[..]), (&buf[..], &other[..]), (&buf[..100], &buf[..]),
+                         (&buf[..], &buf[..33]), (&buf[..0], &buf[..5]), (&buf[..64], &other[..64])] {
+            let (hash_a, hash_b, equal) = hash_compare(a, b, 42);
+            assert_eq!(hash_a, hash_seeded(a, 42));
+            assert_eq!(hash_b, hash_seeded(b, 42));
+            assert_eq!(equal, a == b);
+        }
+    }
+
+    #[test]
+    fn small() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..=16 {
+            assert_eq!(hash_small(&buf[..n], 42), hash_seeded(&buf[..n], 42));
+            assert_eq!(hash_small(&[0; 16][..n], 0), hash_seeded(&[0; 16][..n], 0));
+        }
+    }
+
+    #[test]
+    fn small_fallback() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 17..buf.len() {
+            assert_eq!(hash_small_with_fallback(&buf[..n], 42), hash_seeded(&buf[..n], 42));
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "at most 16 bytes")]
+    fn small_too_long() {
+        hash_small(&[0; 17], 42);
+    }
+
+    #[test]
+    fn alignments() {
+        // Every offset within a word, so that the reads hit every alignment.
+        let mut buf = [0; 8 + 100];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7 + 3) as u8;
+        }
+
+        for offset in 0..8 {
+            for n in 0..100 {
+                let slice = &buf[offset..offset + n];
+                assert_eq!(hash_seeded(slice, 42), reference::hash_seeded(slice, 42));
+            }
+        }
+    }
+
+    #[test]
+    fn u64_array() {
+        fn le_bytes(arr: &[u64]) -> [u8; 64] {
+            let mut bytes = [0; 64];
+            for (chunk, x) in bytes.chunks_mut(8).zip(arr) {
+                chunk.copy_from_slice(&x.to_le_bytes());
+            }
+            bytes
+        }
+
+        let arr = [0x0123456789abcdef, 1, !0, 42, 0, 7, 0xdeadbeef, 1 << 63];
+        let bytes = le_bytes(&arr);
+        assert_eq!(hash_u64_array(&[arr[0]], 1), hash_seeded(&bytes[..8], 1));
+        assert_eq!(hash_u64_array(&[arr[0], arr[1], arr[2]], 1), hash_seeded(&bytes[..24], 1));
+        assert_eq!(hash_u64_array(&[arr[0], arr[1], arr[2], arr[3]], 1), hash_seeded(&bytes[..32], 1));
+        assert_eq!(hash_u64_array(&arr, 1), hash_seeded(&bytes, 1));
+        assert_eq!(hash_u64_array(&[], 1), hash_seeded(&[], 1));
+
+        // The digest is defined by the values, not by their representation in memory: a
+        // big-endian host stores `x` as `x.to_be_bytes()`, but it still hashes the little-endian
+        // encoding, which differs.
+        let x = arr[0];
+        assert_eq!(hash_u64_array(&[x], 1), hash_seeded(&x.to_le_bytes(), 1));
+        assert_ne!(hash_u64_array(&[x], 1), hash_seeded(&x.to_be_bytes(), 1));
+        assert_eq!(hash_u64_array(&[x.swap_bytes()], 1), hash_seeded(&x.to_be_bytes(), 1));
+    }
+
+    #[test]
+    fn ring() {
+        let mut buf = [0; 100];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 5 + 2) as u8;
+        }
+
+        // Wrap at every offset within a word, around and within the blocks.
+        for &base in &[0, 8, 24, 32, 64, 88] {
+            for wrap in base..base + 9 {
+                let (first, second) = buf.split_at(wrap);
+                assert_eq!(hash_ring(first, second, 42), hash_seeded(&buf, 42));
+            }
+        }
+        // Less than a block in total.
+        for n in 0..32 {
+            for wrap in 0..=n {
+                let (first, second) = buf[..n].split_at(wrap);
+                assert_eq!(hash_ring(first, second, 42), reference::hash_seeded(&buf[..n], 42));
+            }
+        }
+    }
+
+    #[test]
+    fn maybe_uninit() {
+        let data = b"love is a wonderful terrible thing";
+        let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+
+        for n in 0..data.len() {
+            buf[n] = MaybeUninit::new(data[n]);
+            unsafe {
+                assert_eq!(hash_maybe_uninit(&buf, n + 1, 1), hash_seeded(&data[..n + 1], 1));
+            }
+        }
+        unsafe {
+            assert_eq!(hash_maybe_uninit(&buf, 0, 1), hash_seeded(&[], 1));
+        }
+    }
 }
@@ -0,0 +1,460 @@
+//! Hashing of input in chunks.
+
+use core::hash::Hasher;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use SeaHasher;
+#[cfg(feature = "alloc")]
+use {combine_many, hash_seeded};
+
+/// The number of bytes hashed between two cancellation checks.
+///
+/// This is a multiple of 32 bytes, so every chunk but the last is processed entirely by the fast
+/// path of the hasher.
+const CANCEL_CHUNK: usize = 1 << 16;
+
+/// Hash some buffer according to a chosen seed, allowing cancellation midway.
+///
+/// The buffer is processed in chunks, and `should_cancel` is polled before each of them. If it
+/// returns `true`, hashing is aborted and `None` is returned. Otherwise, the result is the same as
+/// `hash_seeded(buf, seed)`.
+///
+/// This is intended for hashing very big buffers (e.g. on a worker thread), where the caller wants
+/// to be able to give up early.
+pub fn hash_with_cancel(buf: &[u8], seed: u64, should_cancel: &dyn Fn() -> bool) -> Option<u64> {
+    let mut hasher = SeaHasher::with_seed(seed);
+
+    for chunk in buf.chunks(CANCEL_CHUNK) {
+        if should_cancel() {
+            return None;
+        }
+
+        hasher.write(chunk);
+    }
+
+    Some(hasher.finish())
+}
+
+/// The partial hash of a prefix of some buffer.
+///
+/// This is created by `hash_partial()` and finished with `PartialHash::extend()`. It is a small
+/// `Copy` value, which can be sent to another thread to finish the hash there.
+#[derive(Clone, Copy)]
+pub struct PartialHash {
+    /// The state after the prefix.
+    hasher: SeaHasher,
+}
+
+impl PartialHash {
+    /// Finish the hash with the rest of the buffer.
+    ///
+    /// The result is the same as `hash_seeded()` of the prefix followed by `rest`.
+    pub fn extend(self, rest: &[u8]) -> u64 {
+        let mut hasher = self.hasher;
+        hasher.write(rest);
+
+        hasher.finish()
+    }
+}
+
+/// Hash the prefix of some buffer according to a chosen seed, leaving the rest for later.
+///
+/// The prefix must be a whole number of 32-byte blocks, i.e. its length must be divisible by 32.
+/// The returned state can then be finished with `PartialHash::extend()` (e.g. on another thread),
+/// and splitting at such a boundary is guaranteed to give the same result as hashing the whole
+/// buffer at once:
+///
+/// ```
+/// let buf = [42; 100];
+/// let (prefix, rest) = buf.split_at(64);
+///
+/// assert_eq!(seahash::hash_partial(prefix, 1).extend(rest), seahash::hash_seeded(&buf, 1));
+/// ```
+///
+/// # Panics
+///
+/// This panics if the length of `buf` is not divisible by 32.
+pub fn hash_partial(buf: &[u8], seed: u64) -> PartialHash {
+    assert!(buf.len() & 0x1F == 0, "The prefix must be a whole number of 32-byte blocks.");
+
+    let mut hasher = SeaHasher::with_seed(seed);
+    hasher.write(buf);
+
+    PartialHash { hasher }
+}
+
+/// The size of the stack buffer which `hash_generated()` has filled.
+const GENERATED_BUF_LEN: usize = 1024;
+
+/// Hash bytes produced by a generator according to a chosen seed.
+///
+/// `fill` is called repeatedly with a reusable buffer, and must write the next bytes to the start
+/// of it, returning how many it wrote, or zero when there are no more. The result is the same as
+/// `hash_seeded()` over all the generated bytes, without ever materializing them. The buffer lives
+/// on the stack, so this works without allocation.
+///
+/// # Panics
+///
+/// This panics if `fill` returns more than the length of the buffer it is given.
+pub fn hash_generated<F: FnMut(&mut [u8]) -> usize>(mut fill: F, seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    let mut buf = [0; GENERATED_BUF_LEN];
+
+    loop {
+        let n = fill(&mut buf);
+        if n == 0 {
+            break;
+        }
+
+        hasher.write(&buf[..n]);
+    }
+
+    hasher.finish()
+}
+
+/// Hash a sequence of chunks according to a chosen seed, reporting the progress.
+///
+/// The result is the same as `hash_seeded()` of the concatenation of the chunks. After every chunk,
+/// `progress` is called with the number of bytes hashed so far (including that chunk), e.g. to
+/// update a progress bar when hashing huge inputs.
+pub fn hash_chunks_with_progress<'a, I, F>(chunks: I, seed: u64, mut progress: F) -> u64
+    where I: IntoIterator<Item = &'a [u8]>, F: FnMut(u64) {
+    let mut hasher = SeaHasher::with_seed(seed);
+    let mut total = 0u64;
+    for chunk in chunks {
+        hasher.write(chunk);
+        total += chunk.len() as u64;
+        progress(total);
+    }
+
+    hasher.finish()
+}
+
+/// Hash strided elements of some buffer according to a chosen seed.
+///
+/// This hashes `count` elements of `elem_len` bytes, the `i`'th of which starts at
+/// `offset + i * stride` in `base`, e.g. a single column of a row-major table. The result is the
+/// same as `hash_seeded()` of the elements gathered into a contiguous buffer, but no such buffer is
+/// needed: the elements are streamed into a `SeaHasher`, which gathers them into words itself.
+///
+/// # Panics
+///
+/// This panics if any element is out of bounds of `base`, including if its position overflows
+/// `usize`.
+pub fn hash_strided(base: &[u8], offset: usize, stride: usize, count: usize, elem_len: usize, seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    for i in 0..count {
+        // Check for overflow explicitly, as a wrapped position could be in bounds.
+        let start = i.checked_mul(stride).and_then(|x| x.checked_add(offset))
+            .expect("The element is out of bounds.");
+        let end = start.checked_add(elem_len).expect("The element is out of bounds.");
+        hasher.write(&base[start..end]);
+    }
+
+    hasher.finish()
+}
+
+/// Hash a row of a structure-of-arrays table according to a chosen seed.
+///
+/// Every element of `fields` is a column of elements of `field_len` bytes, and the row `index`
+/// consists of the `index`'th element of every column, in order. The result is the same as
+/// `hash_seeded()` of that row gathered into a contiguous record (i.e. the row of the equivalent
+/// array-of-structures table), but the elements are streamed into a `SeaHasher` instead.
+///
+/// # Panics
+///
+/// This panics if the row is out of bounds of any column, including if its position overflows
+/// `usize`.
+pub fn hash_soa(fields: &[&[u8]], field_len: usize, index: usize, seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    // Check for overflow explicitly, as a wrapped position could be in bounds.
+    let start = index.checked_mul(field_len).expect("The row is out of bounds.");
+    let end = start.checked_add(field_len).expect("The row is out of bounds.");
+    for field in fields {
+        hasher.write(&field[start..end]);
+    }
+
+    hasher.finish()
+}
+
+/// Hash the bytes yielded by a dynamic iterator according to a chosen seed.
+///
+/// The bytes are assembled into words, which are written to a `SeaHasher`, so the result is the
+/// same as `hash_seeded()` of the collected bytes. The iterator is taken as a trait object, so
+/// this is compiled once, rather than for every iterator type, which keeps the code size down
+/// when hashing many kinds of byte streams (e.g. in a plugin system). For a concrete iterator type,
+/// writing to a `SeaHasher` directly is usually faster.
+pub fn hash_dyn_iter(iter: &mut dyn Iterator<Item = u8>, seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+
+    let mut word = 0;
+    let mut n = 0;
+    for byte in iter {
+        word |= (byte as u64) << (8 * n);
+        n += 1;
+
+        if n == 8 {
+            hasher.write_u64(word);
+            word = 0;
+            n = 0;
+        }
+    }
+    hasher.write(&word.to_le_bytes()[..n]);
+
+    hasher.finish()
+}
+
+/// Hash a buffer of fixed-size records, record by record.
+///
+/// The buffer is split into records of `record_len` bytes. The digest of every record (i.e.
+/// `hash_seeded(record, seed)`) is appended to `out`, and the combination of those digests (by
+/// `combine_many()`) is returned as the digest of all the records.
+///
+/// # Panics
+///
+/// This panics if `record_len` is zero or the length of the buffer is not divisible by it.
+#[cfg(feature = "alloc")]
+pub fn hash_records(buf: &[u8], record_len: usize, seed: u64, out: &mut Vec<u64>) -> u64 {
+    assert!(record_len != 0, "The records must be nonempty.");
+    assert!(buf.len() % record_len == 0, "The buffer must consist of whole records.");
+
+    let start = out.len();
+    out.extend(buf.chunks(record_len).map(|record| hash_seeded(record, seed)));
+
+    combine_many(out[start..].iter().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::cell::Cell;
+
+    use hash_seeded;
+
+    #[test]
+    fn complete() {
+        let mut buf = [0; 3 * CANCEL_CHUNK + 5];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7) as u8;
+        }
+
+        assert_eq!(hash_with_cancel(&buf, 42, &|| false), Some(hash_seeded(&buf, 42)));
+        assert_eq!(hash_with_cancel(&[], 42, &|| false), Some(hash_seeded(&[], 42)));
+    }
+
+    #[test]
+    fn partial() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 13) as u8;
+        }
+
+        for split in (0..300).step_by(32) {
+            let (prefix, rest) = buf.split_at(split);
+            assert_eq!(hash_partial(prefix, 42).extend(rest), hash_seeded(&buf, 42));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn partial_unaligned() {
+        hash_partial(&[0; 33], 42);
+    }
+
+    #[test]
+    fn generated() {
+        // A deterministic sequence of bytes from a linear congruential generator.
+        let mut buf = [0; 5000];
+        let mut x = 1u32;
+        for byte in buf.iter_mut() {
+            x = x.wrapping_mul(1103515245).wrapping_add(12345);
+            *byte = (x >> 16) as u8;
+        }
+
+        // Emit the same sequence in chunks of varying sizes.
+        for &max in &[1, 7, 100, GENERATED_BUF_LEN] {
+            let mut pos = 0;
+            let mut round = 0;
+            let digest = hash_generated(|out| {
+                round += 1;
+                let n = (round * 13 % max + 1).min(out.len()).min(buf.len() - pos);
+                out[..n].copy_from_slice(&buf[pos..pos + n]);
+                pos += n;
+                n
+            }, 42);
+
+            assert_eq!(digest, hash_seeded(&buf, 42));
+        }
+
+        assert_eq!(hash_generated(|_| 0, 42), hash_seeded(&[], 42));
+    }
+
+    #[test]
+    #[should_panic]
+    fn generated_overflow() {
+        hash_generated(|out| out.len() + 1, 42);
+    }
+
+    #[test]
+    fn strided() {
+        // A table of 7 rows of 10 bytes, with a 3-byte column at offset 4.
+        let mut table = [0; 7 * 10];
+        for (i, x) in table.iter_mut().enumerate() {
+            *x = (i * 11) as u8;
+        }
+
+        let mut column = [0; 7 * 3];
+        for (row, elem) in table.chunks(10).zip(column.chunks_mut(3)) {
+            elem.copy_from_slice(&row[4..7]);
+        }
+
+        assert_eq!(hash_strided(&table, 4, 10, 7, 3, 42), hash_seeded(&column, 42));
+        assert_eq!(hash_strided(&table, 4, 10, 0, 3, 42), hash_seeded(&[], 42));
+        assert_eq!(hash_strided(&table, 0, 10, 7, 10, 42), hash_seeded(&table, 42));
+    }
+
+    #[test]
+    fn progress() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7) as u8;
+        }
+        let chunks = [&buf[..5], &buf[5..5], &buf[5..200], &buf[200..]];
+
+        let mut reported = [0; 4];
+        let mut calls = 0;
+        let digest = hash_chunks_with_progress(chunks.iter().cloned(), 42, |n| {
+            reported[calls] = n;
+            calls += 1;
+        });
+
+        assert_eq!(digest, hash_seeded(&buf, 42));
+        assert_eq!(calls, 4);
+        assert_eq!(reported, [5, 5, 200, 300]);
+        assert!(reported.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let none: [&[u8]; 0] = [];
+        let digest = hash_chunks_with_progress(none.iter().cloned(), 42, |_| panic!());
+        assert_eq!(digest, hash_seeded(&[], 42));
+    }
+
+    #[test]
+    fn soa() {
+        // A table of 5 rows of three 4-byte fields, as an array of structures.
+        let mut aos = [0; 5 * 12];
+        for (i, x) in aos.iter_mut().enumerate() {
+            *x = (i * 13 + 1) as u8;
+        }
+
+        // The same table as three columns.
+        let mut columns = [[0; 5 * 4]; 3];
+        for (row, record) in aos.chunks(12).enumerate() {
+            for (column, field) in columns.iter_mut().zip(record.chunks(4)) {
+                column[row * 4..row * 4 + 4].copy_from_slice(field);
+            }
+        }
+        let fields = [&columns[0][..], &columns[1][..], &columns[2][..]];
+
+        for (row, record) in aos.chunks(12).enumerate() {
+            assert_eq!(hash_soa(&fields, 4, row, 42), hash_seeded(record, 42));
+        }
+        assert_eq!(hash_soa(&[], 4, 0, 42), hash_seeded(&[], 42));
+        assert_ne!(hash_soa(&fields, 4, 0, 42), hash_soa(&fields, 4, 1, 42));
+    }
+
+    #[test]
+    #[should_panic]
+    fn soa_out_of_bounds() {
+        hash_soa(&[&[0; 8], &[0; 4]], 4, 1, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn soa_overflow() {
+        // The position of the row would wrap around to 0.
+        hash_soa(&[&[0; 8], &[0; 8]], 2, 1 << (usize::BITS - 1), 42);
+    }
+
+    #[test]
+    #[should_panic]
+    fn strided_out_of_bounds() {
+        hash_strided(&[0; 20], 4, 10, 2, 8, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn strided_overflow() {
+        // The second element would wrap around to offset 0.
+        hash_strided(&[0; 20], 1, usize::MAX, 2, 1, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn strided_end_overflow() {
+        hash_strided(&[0; 20], 2, 0, 1, usize::MAX, 42);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn dyn_iter() {
+        use alloc::boxed::Box;
+
+        let mut buf = [0; 100];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 3 + 1) as u8;
+        }
+
+        for n in 0..100 {
+            let mut iter: Box<dyn Iterator<Item = u8>> = Box::new(buf[..n].iter().cloned());
+            assert_eq!(hash_dyn_iter(&mut *iter, 42), hash_seeded(&buf[..n], 42));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn records() {
+        let mut buf = [0; 12 * 10];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 17) as u8;
+        }
+
+        let mut out = Vec::new();
+        out.push(1234);
+        let combined = hash_records(&buf, 12, 42, &mut out);
+
+        assert_eq!(out.len(), 11);
+        assert_eq!(out[0], 1234);
+        for (record, &h) in buf.chunks(12).zip(&out[1..]) {
+            assert_eq!(h, hash_seeded(record, 42));
+        }
+        assert_eq!(combined, combine_many(out[1..].iter().cloned()));
+
+        let mut swapped = buf;
+        swapped[..12].copy_from_slice(&buf[12..24]);
+        swapped[12..24].copy_from_slice(&buf[..12]);
+        assert_ne!(hash_records(&swapped, 12, 42, &mut Vec::new()), combined);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[should_panic]
+    fn records_partial() {
+        hash_records(&[0; 13], 12, 42, &mut Vec::new());
+    }
+
+    #[test]
+    fn cancel() {
+        let buf = [1; 3 * CANCEL_CHUNK];
+        let polls = Cell::new(0);
+
+        let result = hash_with_cancel(&buf, 42, &|| {
+            polls.set(polls.get() + 1);
+            polls.get() == 2
+        });
+
+        assert_eq!(result, None);
+        assert_eq!(polls.get(), 2);
+    }
+}
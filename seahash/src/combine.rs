@@ -0,0 +1,207 @@
+//! Combination of digests.
+
+use core::hash::Hasher;
+
+use {SeaHasher, diffuse, hash_seeded};
+use consts::{DEFAULT_SEED, INIT_B};
+
+/// Combine a sequence of digests into one.
+///
+/// The result is the hash (with the default seed) of the digests written as 64-bit little-endian
+/// integers one after another, i.e. equal to `hash()` of their concatenated little-endian bytes.
+/// As such, it depends on the order of the digests.
+pub fn combine_many<I: IntoIterator<Item = u64>>(hashes: I) -> u64 {
+    let mut hasher = SeaHasher::new();
+    for h in hashes {
+        hasher.write_u64(h);
+    }
+
+    hasher.finish()
+}
+
+/// Combine the digests of the items of an unordered set into one.
+///
+/// Every digest is diffused (after XOR'ing in a constant, so that a digest of zero, the fixed
+/// point of the diffusion function, still contributes) and the results are added up, wrapping.
+/// As addition is commutative, any permutation of the items gives the same result. The number of
+/// items is then mixed into the sum, which is diffused once more.
+///
+/// The collision properties are weaker than those of `combine_many()`: the combination is linear,
+/// so an adversary who can choose the item digests can easily find distinct sets with the same
+/// digest. For items which are not chosen adversarially, two given distinct sets collide with a
+/// probability of about 2^-64. Duplicates are counted (the digest is one of a multiset), rather
+/// than cancelling each other out as they would with XOR.
+pub fn set_digest<I: IntoIterator<Item = u64>>(item_hashes: I) -> u64 {
+    let mut sum = 0u64;
+    let mut count = 0u64;
+    for h in item_hashes {
+        sum = sum.wrapping_add(diffuse(h ^ INIT_B));
+        count = count.wrapping_add(1);
+    }
+
+    diffuse(sum ^ diffuse(count ^ DEFAULT_SEED))
+}
+
+/// A chunk which did not match its expected digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkMismatch {
+    /// The index of the chunk (counting from 0).
+    pub index: usize,
+    /// The expected digest.
+    pub expected: u64,
+    /// The actual digest of the chunk.
+    pub actual: u64,
+}
+
+/// A verifier of a stream of chunks against their expected digests.
+///
+/// Every chunk is hashed with `hash_seeded()` and compared against its expected digest as it
+/// arrives, e.g. for a chunked transfer where the sender includes per-chunk digests. Along the
+/// way, the digests are combined into a digest of the whole stream, which is the same as
+/// `combine_many()` of the chunk digests.
+///
+/// Verification short-circuits: after the first mismatch, every further chunk is rejected (without
+/// being hashed) with the same error.
+#[derive(Clone, Copy)]
+pub struct ChunkVerifier {
+    /// The seed of the chunk digests.
+    seed: u64,
+    /// The combination of the digests of the chunks verified so far.
+    combined: SeaHasher,
+    /// The number of chunks verified so far.
+    chunks: usize,
+    /// The first mismatch, if any.
+    mismatch: Option<ChunkMismatch>,
+}
+
+impl Default for ChunkVerifier {
+    fn default() -> ChunkVerifier {
+        ChunkVerifier::new()
+    }
+}
+
+impl ChunkVerifier {
+    /// Create a verifier of chunks hashed with the default seed (matching `hash()`).
+    pub fn new() -> ChunkVerifier {
+        ChunkVerifier::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a verifier of chunks hashed with some seed (matching `hash_seeded()`).
+    pub fn with_seed(seed: u64) -> ChunkVerifier {
+        ChunkVerifier {
+            seed,
+            combined: SeaHasher::new(),
+            chunks: 0,
+            mismatch: None,
+        }
+    }
+
+    /// Verify the next chunk against its expected digest.
+    pub fn verify(&mut self, chunk: &[u8], expected: u64) -> Result<(), ChunkMismatch> {
+        if let Some(mismatch) = self.mismatch {
+            return Err(mismatch);
+        }
+
+        let actual = hash_seeded(chunk, self.seed);
+        if actual != expected {
+            let mismatch = ChunkMismatch {
+                index: self.chunks,
+                expected,
+                actual,
+            };
+            self.mismatch = Some(mismatch);
+
+            return Err(mismatch);
+        }
+
+        self.combined.write_u64(actual);
+        self.chunks += 1;
+
+        Ok(())
+    }
+
+    /// Get the number of chunks verified successfully.
+    pub fn chunks(&self) -> usize {
+        self.chunks
+    }
+
+    /// Get the combined digest of the chunks verified successfully.
+    pub fn digest(&self) -> u64 {
+        self.combined.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hash;
+
+    const CHUNKS: [&[u8]; 3] = [b"to be", b" or not", b" to be"];
+
+    #[test]
+    fn many() {
+        let mut bytes = [0; 24];
+        bytes[..8].copy_from_slice(&1u64.to_le_bytes());
+        bytes[8..16].copy_from_slice(&2u64.to_le_bytes());
+        bytes[16..].copy_from_slice(&3u64.to_le_bytes());
+
+        assert_eq!(combine_many([1, 2, 3].iter().cloned()), hash(&bytes));
+        assert_ne!(combine_many([1, 2, 3].iter().cloned()), combine_many([3, 2, 1].iter().cloned()));
+        assert_eq!(combine_many(None), hash(&[]));
+    }
+
+    #[test]
+    fn verify_correct() {
+        let mut verifier = ChunkVerifier::with_seed(42);
+        for chunk in CHUNKS.iter() {
+            assert_eq!(verifier.verify(chunk, hash_seeded(chunk, 42)), Ok(()));
+        }
+
+        assert_eq!(verifier.chunks(), 3);
+        let digests = CHUNKS.iter().map(|chunk| hash_seeded(chunk, 42));
+        assert_eq!(verifier.digest(), combine_many(digests));
+    }
+
+    #[test]
+    fn verify_corrupt() {
+        let mut verifier = ChunkVerifier::new();
+        assert_eq!(verifier.verify(CHUNKS[0], hash(CHUNKS[0])), Ok(()));
+
+        let mismatch = ChunkMismatch {
+            index: 1,
+            expected: hash(b" or nat"),
+            actual: hash(CHUNKS[1]),
+        };
+        assert_eq!(verifier.verify(CHUNKS[1], hash(b" or nat")), Err(mismatch));
+        // Everything after the first mismatch is rejected.
+        assert_eq!(verifier.verify(CHUNKS[2], hash(CHUNKS[2])), Err(mismatch));
+
+        assert_eq!(verifier.chunks(), 1);
+        assert_eq!(verifier.digest(), combine_many(Some(hash(CHUNKS[0]))));
+    }
+
+    #[test]
+    fn verify_empty() {
+        let verifier = ChunkVerifier::new();
+
+        assert_eq!(verifier.chunks(), 0);
+        assert_eq!(verifier.digest(), combine_many(None));
+    }
+
+    #[test]
+    fn set() {
+        let items = [hash(b"apple"), hash(b"banana"), hash(b"cherry"), 0];
+        let digest = set_digest(items.iter().cloned());
+
+        assert_eq!(set_digest([items[3], items[1], items[0], items[2]].iter().cloned()), digest);
+        assert_eq!(set_digest(items.iter().rev().cloned()), digest);
+        assert_ne!(set_digest(items.iter().cloned()), combine_many(items.iter().cloned()));
+
+        // Adding or removing an element changes the digest, even if its digest is zero.
+        assert_ne!(set_digest(items[..3].iter().cloned()), digest);
+        assert_ne!(set_digest(items.iter().cloned().chain(Some(hash(b"date")))), digest);
+        assert_ne!(set_digest(items.iter().cloned().chain(Some(items[0]))), digest);
+        assert_ne!(set_digest(None), set_digest(Some(0)));
+    }
+}
@@ -0,0 +1,102 @@
+//! The constants of SeaHash.
+//!
+//! Every implementation in this crate takes its constants from here, such that they cannot drift
+//! apart. See the `reference` module for the specification.
+
+/// The multiplier of the diffusion function, `p`.
+pub const P: u64 = 0x7ed0e9fa0d94a33;
+/// The multiplicative inverse of `P` modulo 2^64, used to invert the diffusion function.
+pub const P_INV: u64 = 0x79b24db62d7ac0fb;
+/// The default seed, i.e. the initial value of the first component of the state vector.
+pub const DEFAULT_SEED: u64 = 0x16f11fe89b0d677c;
+/// The initial value of the second component of the state vector.
+pub const INIT_B: u64 = 0xb480a793d8e6c86c;
+/// The initial value of the third component of the state vector.
+pub const INIT_C: u64 = 0x6fe2e5aaf078ebc9;
+/// The initial value of the fourth component of the state vector.
+pub const INIT_D: u64 = 0x14f994a4c5259381;
+/// The multiplier of the block index in the tweak of the boosted variant, `t`.
+pub const BOOST_TWEAK: u64 = 0x9e3779b97f4a7c15;
+
+/// The default seed of the `SeaHasher` of versions up to 2.1.1 (see `SeaHasherLegacy`).
+#[cfg(feature = "legacy")]
+pub const LEGACY_HASHER_SEED: u64 = 0xe7b0c93ca8525013;
+/// The initial state of the `SeaHasher` of versions up to 2.1.1 (see `SeaHasherLegacy`).
+#[cfg(feature = "legacy")]
+pub const LEGACY_HASHER_STATE: u64 = 0xba663d61fe3aa408;
+
+/// The version of the algorithm.
+///
+/// This is bumped if and only if the output of the algorithm changes (for any input, seed, or
+/// entry point), so it can be stored alongside digests in order to detect that they were computed
+/// by an incompatible version. Changes which do not affect the output never bump it.
+///
+/// Version 1 is the output of seahash 3.0.0. `hash()` and `hash_seeded()` give the same digests as
+/// in all earlier versions (on 64-bit targets), but the `SeaHasher` of versions up to 2.1.1 chained
+/// its writes, and its digests are not described by any algorithm version (they are reproduced by
+/// `SeaHasherLegacy` instead).
+pub const ALGORITHM_VERSION: u32 = 1;
+/// The name of the algorithm, including its version.
+///
+/// This changes together with `ALGORITHM_VERSION`.
+pub const ALGORITHM_NAME: &str = "SeaHash-1";
+
+// Check the structural requirements on the constants at compile time. Their values are pinned by
+// the known-answer tests below.
+// The multiplier must be odd, or the diffusion function would not be bijective.
+const _: () = assert!(P & 1 == 1);
+// `P_INV` must be the inverse of `P`, or `undiffuse()` would not invert `diffuse()`.
+const _: () = assert!(P.wrapping_mul(P_INV) == 1);
+// The initial state components must be mutually distinct, or swapping blocks between them would
+// not affect the result.
+const _: () = assert!(DEFAULT_SEED != INIT_B && DEFAULT_SEED != INIT_C && DEFAULT_SEED != INIT_D);
+const _: () = assert!(INIT_B != INIT_C && INIT_B != INIT_D && INIT_C != INIT_D);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use {buffer, reference, SeaHasher};
+    use core::hash::Hasher;
+
+    #[test]
+    fn implementations_agree() {
+        // A mismatch in any constant would make the implementations disagree on these.
+        let inputs: [&[u8]; 4] = [b"", b"to be or not to be", &[0xFF; 37], &[0; 100]];
+        for input in inputs.iter() {
+            let mut stream = SeaHasher::new();
+            stream.write(input);
+
+            assert_eq!(buffer::hash(input), reference::hash(input));
+            assert_eq!(stream.finish(), reference::hash(input));
+        }
+
+        // Pin the output, such that a change to a constant in all implementations is caught too.
+        assert_eq!(reference::hash(b"to be or not to be"), 16114993074217697639);
+    }
+
+    #[test]
+    fn known_answers() {
+        // Computed from the specification, independently of this crate. Every constant affects
+        // at least one of these: the empty input folds the initial state, seed zero isolates
+        // `INIT_B`, `INIT_C` and `INIT_D` from `DEFAULT_SEED`, 32 bytes go through every lane,
+        // and the boosted variant multiplies the block indices by `BOOST_TWEAK`.
+        let mut buf = [0; 40];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = i as u8;
+        }
+
+        assert_eq!(reference::hash(b""), 7745307271276305516);
+        assert_eq!(reference::hash_seeded(b"", 0), 2509897130690332299);
+        assert_eq!(reference::hash_seeded(&buf[..32], 0), 5635496136222896404);
+        assert_eq!(reference::hash(&buf), 13993874436240022902);
+        assert_eq!(reference::hash_boosted(&buf, 0), 1894040276061014330);
+    }
+
+    #[test]
+    fn version() {
+        // Changing these is only allowed together with a change of the output, see above.
+        assert_eq!(ALGORITHM_VERSION, 1);
+        assert_eq!(ALGORITHM_NAME, "SeaHash-1");
+    }
+}
@@ -0,0 +1,137 @@
+//! Empirical checks of the statistical quality.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use {diffuse, hash_seeded};
+use reference;
+
+/// Count the 64-bit collisions among the digests of pseudorandom keys.
+///
+/// This hashes `count` distinct keys of `key_len` bytes with `hash_seeded(key, seed)`, and returns
+/// the number of keys whose digest equals the digest of an earlier key. The keys are generated
+/// deterministically from `seed`, so the result is reproducible.
+///
+/// For an ideal 64-bit hash function, the expected number of collisions is about
+/// `count² / 2⁶⁵`, i.e. practically zero for any `count` much below 2³².
+///
+/// This uses `O(count + key_len)` memory.
+///
+/// # Panics
+///
+/// This panics if there are fewer than `count` distinct keys of `key_len` bytes.
+pub fn birthday_collisions(seed: u64, key_len: usize, count: usize) -> usize {
+    assert!(key_len >= 8 || count == 0 || (count as u64 - 1) >> (8 * key_len) == 0,
+            "There are not enough distinct keys of the given length.");
+
+    let mut key = vec![0; key_len];
+    let mut digests = Vec::with_capacity(count);
+    for i in 0..count as u64 {
+        // The first word tells the keys apart: `diffuse()` is bijective, so the keys are
+        // distinct. Keys shorter than a word simply get the index.
+        let mut x = if key_len >= 8 { diffuse(seed ^ i) } else { i };
+        for word in key.chunks_mut(8) {
+            word.copy_from_slice(&x.to_le_bytes()[..word.len()]);
+            // The rest of the key is pseudorandom.
+            x = diffuse(x ^ 0x9e3779b97f4a7c15);
+        }
+
+        digests.push(hash_seeded(&key, seed));
+    }
+
+    digests.sort_unstable();
+    digests.windows(2).filter(|pair| pair[0] == pair[1]).count()
+}
+
+/// A block written to the state by `explain()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The block, i.e. the next 8 bytes of the input (zero-padded) in little-endian.
+    pub block: u64,
+    /// The lane which the block was mixed into.
+    pub lane: usize,
+    /// The state of the lane after mixing the block in.
+    pub lane_state: u64,
+}
+
+/// A report of how the digest of some input came about, created by `explain()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// Every block of the input, in order.
+    pub steps: Vec<Step>,
+    /// The final state vector.
+    pub lanes: [u64; 4],
+    /// The length of the input, which is folded in with the lanes.
+    pub len: u64,
+    /// The folded state, `a ^ b ^ c ^ d ^ len`, before the final diffusion.
+    pub folded: u64,
+    /// The digest, i.e. `diffuse(folded)`.
+    pub hash: u64,
+}
+
+/// Explain the digest of some buffer, block by block.
+///
+/// This runs the reference implementation on `buf` with the given seed, recording every 8-byte
+/// block, the lane it went to and the state of that lane afterwards. This is meant for debugging,
+/// e.g. to see which lanes two colliding inputs differ in.
+pub fn explain(buf: &[u8], seed: u64) -> Explanation {
+    let mut steps = Vec::with_capacity(buf.len().div_ceil(8));
+    let (lanes, hash) = reference::hash_traced(buf, seed, |block, lane, lane_state| {
+        steps.push(Step { block, lane, lane_state });
+    });
+
+    let len = buf.len() as u64;
+    Explanation {
+        steps,
+        lanes,
+        len,
+        folded: lanes[0] ^ lanes[1] ^ lanes[2] ^ lanes[3] ^ len,
+        hash,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_collisions() {
+        // The expected number of collisions is about 3·10⁻¹⁰.
+        assert_eq!(birthday_collisions(42, 16, 100000), 0);
+        assert_eq!(birthday_collisions(42, 3, 100000), 0);
+        assert_eq!(birthday_collisions(42, 1, 256), 0);
+        assert_eq!(birthday_collisions(42, 0, 1), 0);
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(birthday_collisions(7, 33, 1000), birthday_collisions(7, 33, 1000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_few_keys() {
+        birthday_collisions(42, 1, 257);
+    }
+
+    #[test]
+    fn explained() {
+        let buf = b"to be or not to be";
+        let explanation = explain(buf, 42);
+
+        assert_eq!(explanation.steps.len(), buf.len().div_ceil(8));
+        assert_eq!(explanation.hash, reference::hash_seeded(buf, 42));
+        assert_eq!(diffuse(explanation.folded), explanation.hash);
+
+        // The blocks go round-robin, and the last write to a lane is its final state.
+        for (i, step) in explanation.steps.iter().enumerate() {
+            assert_eq!(step.lane, i % 4);
+            assert_eq!(step.lane_state, explanation.lanes[i % 4]);
+        }
+        assert_eq!(explanation.steps[2].block, u64::from_le_bytes(*b"be\0\0\0\0\0\0"));
+
+        let empty = explain(b"", 42);
+        assert!(empty.steps.is_empty());
+        assert_eq!(empty.hash, reference::hash_seeded(b"", 42));
+    }
+}
@@ -0,0 +1,191 @@
+//! Wrappers around digests.
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use {hash, hash_seeded};
+
+/// A SeaHash digest.
+///
+/// This is a thin wrapper around the 64-bit digest. Its `Hash` implementation writes the digest
+/// as a single `u64`, so using it as the key of another hash map doesn't hash the bytes twice.
+///
+/// It is displayed as 16 lowercase hexadecimal digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SeaDigest(pub u64);
+
+impl Hash for SeaDigest {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0);
+    }
+}
+
+impl fmt::Display for SeaDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl fmt::LowerHex for SeaDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+/// A 64-bit hash, for printing and comparison.
+///
+/// This makes the intent of a `u64` explicit, and formats it consistently: it is displayed as 16
+/// lowercase hexadecimal digits, and it supports `{:x}` and `{:X}`. It compares equal to the raw
+/// `u64` it wraps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hash64(pub u64);
+
+impl From<u64> for Hash64 {
+    fn from(x: u64) -> Hash64 {
+        Hash64(x)
+    }
+}
+
+impl PartialEq<u64> for Hash64 {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for Hash64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl fmt::LowerHex for Hash64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for Hash64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+/// Hash some buffer, wrapping the digest in a `Hash64`.
+///
+/// This is the same as `hash()`, except for the return type.
+pub fn hash_wrapped(buf: &[u8]) -> Hash64 {
+    Hash64(hash(buf))
+}
+
+/// A digest ordered the same way on every platform.
+///
+/// This holds the digest in big-endian byte order, and compares the bytes lexicographically, so
+/// the ordering is the numeric ordering of the digest, independently of the byte order of the
+/// platform. This makes it suitable for e.g. sorting records by their hash, with the same order
+/// everywhere. The bytes can also be stored as is, preserving the order under byte-wise
+/// comparison.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OrderedHash(pub [u8; 8]);
+
+impl OrderedHash {
+    /// Hash some buffer according to a chosen seed (matching `hash_seeded()`).
+    pub fn of(buf: &[u8], seed: u64) -> OrderedHash {
+        OrderedHash::from(hash_seeded(buf, seed))
+    }
+
+    /// Get the digest.
+    pub fn digest(&self) -> u64 {
+        u64::from_be_bytes(self.0)
+    }
+}
+
+impl From<u64> for OrderedHash {
+    fn from(x: u64) -> OrderedHash {
+        OrderedHash(x.to_be_bytes())
+    }
+}
+
+impl From<OrderedHash> for u64 {
+    fn from(x: OrderedHash) -> u64 {
+        x.digest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn map() {
+        let mut map = HashMap::new();
+        for i in 0..1000u32 {
+            map.insert(SeaDigest(hash(&i.to_le_bytes())), i);
+        }
+
+        for i in 0..1000u32 {
+            assert_eq!(map[&SeaDigest(hash(&i.to_le_bytes()))], i);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn format() {
+        assert_eq!(SeaDigest(0xdeadbeef).to_string(), "00000000deadbeef");
+        assert_eq!(SeaDigest(!0).to_string(), "ffffffffffffffff");
+        assert_eq!(format!("{:x}", SeaDigest(0xdeadbeef)), "deadbeef");
+        assert_eq!(format!("{:#018x}", SeaDigest(0xdeadbeef)), "0x00000000deadbeef");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash64_format() {
+        assert_eq!(Hash64(0xdeadbeef).to_string(), "00000000deadbeef");
+        assert_eq!(Hash64(!0).to_string(), "ffffffffffffffff");
+        assert_eq!(Hash64(0).to_string().len(), 16);
+        assert_eq!(format!("{:x}", Hash64(0xdeadbeef)), "deadbeef");
+        assert_eq!(format!("{:X}", Hash64(0xdeadbeef)), "DEADBEEF");
+        assert_eq!(format!("{:016X}", Hash64(0xdeadbeef)), "00000000DEADBEEF");
+    }
+
+    #[test]
+    fn hash64_eq() {
+        assert_eq!(hash_wrapped(b"to be or not to be"), hash(b"to be or not to be"));
+        assert!(Hash64(42) == 42);
+        assert!(Hash64(42) != 43);
+        assert_eq!(Hash64::from(42), Hash64(42));
+    }
+
+    #[test]
+    fn round_trip() {
+        let h = OrderedHash::of(b"to be or not to be", 42);
+
+        assert_eq!(h.digest(), hash_seeded(b"to be or not to be", 42));
+        assert_eq!(u64::from(h), h.digest());
+        assert_eq!(OrderedHash::from(h.digest()), h);
+    }
+
+    #[test]
+    fn order() {
+        let mut naive_disagrees = false;
+
+        for i in 0..100u32 {
+            for j in 0..100u32 {
+                let a = hash_seeded(&i.to_le_bytes(), 42);
+                let b = hash_seeded(&j.to_le_bytes(), 42);
+
+                // The order is the one of the digests, which doesn't depend on the platform.
+                assert_eq!(OrderedHash::from(a).cmp(&OrderedHash::from(b)), a.cmp(&b));
+
+                // Comparing the bytes in the other order (as a naive comparison of the native
+                // bytes would on a little-endian machine) gives a different order.
+                naive_disagrees |= a.to_le_bytes().cmp(&b.to_le_bytes()) != a.cmp(&b);
+            }
+        }
+
+        assert!(naive_disagrees);
+    }
+}
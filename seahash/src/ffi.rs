@@ -0,0 +1,59 @@
+//! Hashing of C and OS strings.
+
+use std::ffi::{CStr, OsStr};
+
+use hash_seeded;
+
+/// Hash a C string according to a chosen seed.
+///
+/// This hashes the bytes of the string **without** the NUL terminator, i.e. the result is the same
+/// as `hash_seeded(s.to_bytes(), seed)`. As such, a C string hashes like the same bytes in a Rust
+/// string or slice.
+pub fn hash_cstr(s: &CStr, seed: u64) -> u64 {
+    hash_seeded(s.to_bytes(), seed)
+}
+
+/// Hash an OS string according to a chosen seed.
+///
+/// This hashes the encoded bytes of the string (`OsStr::as_encoded_bytes()`), i.e. the result is
+/// the same as `hash_seeded(s.as_encoded_bytes(), seed)`. The encoding is platform-dependent
+/// (e.g. arbitrary bytes on Unix, but WTF-8 on Windows), so the same path can hash differently on
+/// different platforms, and the digests should not be persisted across them. Strings which are
+/// valid UTF-8 are encoded as such everywhere, so they hash like the corresponding `str`.
+pub fn hash_osstr(s: &OsStr, seed: u64) -> u64 {
+    hash_seeded(s.as_encoded_bytes(), seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hash;
+    use consts::DEFAULT_SEED;
+
+    #[test]
+    fn cstr() {
+        let s = CStr::from_bytes_with_nul(b"abc\0").unwrap();
+
+        assert_eq!(hash_cstr(s, DEFAULT_SEED), hash(b"abc"));
+        assert_ne!(hash_cstr(s, DEFAULT_SEED), hash(b"abc\0"));
+        assert_eq!(hash_cstr(CStr::from_bytes_with_nul(b"\0").unwrap(), 42), hash_seeded(b"", 42));
+    }
+
+    #[test]
+    fn osstr() {
+        let s = OsStr::new("love is a wonderful terrible thing");
+
+        assert_eq!(hash_osstr(s, 42), hash_seeded(s.as_encoded_bytes(), 42));
+        assert_eq!(hash_osstr(s, 42), hash_seeded(b"love is a wonderful terrible thing", 42));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn osstr_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let s = OsStr::from_bytes(&[0x66, 0xFF, 0x6F]);
+        assert_eq!(hash_osstr(s, 42), hash_seeded(&[0x66, 0xFF, 0x6F], 42));
+    }
+}
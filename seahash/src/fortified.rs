@@ -0,0 +1,86 @@
+//! A keyed mode with a keyed finalization.
+
+use core::hash::Hasher;
+
+use {diffuse, SeaHasher};
+use consts::{INIT_B, INIT_C, INIT_D};
+
+/// Hash some buffer under a 256-bit key, with a keyed final diffusion.
+///
+/// Every word of the key goes into one lane of the initial state (XORed with the usual initial
+/// values), and the digest is diffused once more together with a word derived from the whole key.
+/// As such, the output is not simply the fold of the state, so neither the state nor a digest of
+/// an extended input can be computed from a digest by continuing the plain algorithm (e.g. by
+/// seeding it with the digest), which is what `hash_seeded()` is open to.
+///
+/// **This is a best-effort hardening, not a cryptographic MAC.** SeaHash is not designed to resist
+/// attackers, and this mode has not been analyzed as such. Use a real MAC (e.g. HMAC or
+/// SipHash-based) where forgery matters.
+pub fn hash_fortified(buf: &[u8], key: [u64; 4]) -> u64 {
+    let mut hasher = SeaHasher::with_state([
+        key[0],
+        INIT_B ^ key[1],
+        INIT_C ^ key[2],
+        INIT_D ^ key[3],
+    ]);
+    hasher.write(buf);
+
+    diffuse(hasher.finish() ^ finalization_key(key))
+}
+
+/// Derive the word of the final diffusion from the key, depending on all of its words.
+fn finalization_key(key: [u64; 4]) -> u64 {
+    key.iter().fold(0, |k, &word| diffuse(k ^ word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hash_seeded;
+
+    const KEY: [u64; 4] = [0x0123456789abcdef, 0xfedcba9876543210, 42, !0];
+
+    #[test]
+    fn keyed() {
+        let data = b"to be or not to be";
+
+        assert_eq!(hash_fortified(data, KEY), hash_fortified(data, KEY));
+        assert_ne!(hash_fortified(data, KEY), hash_fortified(b"to be or not to bE", KEY));
+        // Every word of the key matters.
+        for i in 0..4 {
+            let mut key = KEY;
+            key[i] ^= 1;
+            assert_ne!(hash_fortified(data, key), hash_fortified(data, KEY));
+        }
+    }
+
+    #[test]
+    fn not_the_plain_fold() {
+        let data = b"to be or not to be";
+
+        // With the first key word as seed and the rest zero, the state is that of
+        // `hash_seeded()`, but the final diffusion makes the digest differ.
+        assert_ne!(hash_fortified(data, [42, 0, 0, 0]), hash_seeded(data, 42));
+    }
+
+    #[test]
+    fn continuation() {
+        let data = b"love is a wonderful terrible thing";
+        let suffix = b", to be or not to be";
+        let mut extended = [0; 54];
+        extended[..34].copy_from_slice(data);
+        extended[34..].copy_from_slice(suffix);
+
+        let digest = hash_fortified(data, KEY);
+        let expected = hash_fortified(&extended, KEY);
+
+        // Continuing from the digest, whether as the seed or as every lane, doesn't give the
+        // digest of the extended input.
+        assert_ne!(hash_seeded(suffix, digest), expected);
+        let mut hasher = SeaHasher::with_state([digest; 4]);
+        hasher.write(suffix);
+        assert_ne!(hasher.finish(), expected);
+        assert_ne!(diffuse(hasher.finish() ^ finalization_key(KEY)), expected);
+    }
+}
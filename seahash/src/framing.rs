@@ -0,0 +1,160 @@
+//! Hashing of framed sequences of byte strings.
+
+use core::hash::Hasher;
+
+use SeaHasher;
+
+/// Hash a buffer under a domain-separation tag.
+///
+/// This hashes the length of `tag` (as a 64-bit little-endian integer), then `tag`, then `buf`,
+/// all with the given seed, without copying them into a combined buffer. Due to the length
+/// prefix, no two distinct `(tag, buf)` pairs are framed to the same bytes, so moving the boundary
+/// between the tag and the data changes the result. Distinct tags thus give independent hash
+/// functions, which is useful for e.g. versioning hash namespaces.
+pub fn hash_tagged(tag: &[u8], buf: &[u8], seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    hasher.write_u64(tag.len() as u64);
+    hasher.write(tag);
+    hasher.write(buf);
+
+    hasher.finish()
+}
+
+/// Hash a buffer under a schema version.
+///
+/// This hashes the version byte followed by `buf`, with the given seed. As the version has a fixed
+/// width, the framing is unambiguous, so changing the version changes the digest even for the
+/// same data. This allows e.g. readers of an on-disk index to detect digests written under another
+/// schema. Note that this is a distinct hash function, unrelated to `hash_seeded()` of the same
+/// data.
+pub fn hash_versioned(version: u8, buf: &[u8], seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    hasher.write_u8(version);
+    hasher.write(buf);
+
+    hasher.finish()
+}
+
+/// Hash a pair of byte strings (e.g. a key and a value) according to a chosen seed.
+///
+/// Each part is prefixed with its length (as a 64-bit little-endian integer), so the pair is
+/// unambiguous: `("ab", "c")` and `("a", "bc")` hash differently. The parts are hashed in one pass,
+/// without copying them into a combined buffer.
+pub fn hash_pair_seeded(a: &[u8], b: &[u8], seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    hasher.write_u64(a.len() as u64);
+    hasher.write(a);
+    hasher.write_u64(b.len() as u64);
+    hasher.write(b);
+
+    hasher.finish()
+}
+
+/// Hash a record consisting of a header and a body according to a chosen seed.
+///
+/// This is `hash_pair_seeded(header, body, seed)`: the header is hashed first, and each part is
+/// prefixed with its length, so moving bytes from the header to the body (or vice versa) changes
+/// the digest. The header-first order is guaranteed, so e.g. the digest of a record can be
+/// verified against a `SeaHasher` fed the header (with its length) before the body arrives.
+pub fn hash_framed2(header: &[u8], body: &[u8], seed: u64) -> u64 {
+    hash_pair_seeded(header, body, seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use {hash, hash_seeded};
+
+    #[test]
+    fn tagged() {
+        let data = b"to be or not to be";
+
+        assert_ne!(hash_tagged(b"v1", data, 0), hash_tagged(b"v2", data, 0));
+        assert_ne!(hash_tagged(b"", data, 0), hash_seeded(data, 0));
+        assert_ne!(hash_tagged(b"v1", data, 0x16f11fe89b0d677c), hash(data));
+        assert_ne!(hash_tagged(b"v1", data, 0), hash_tagged(b"v1", data, 1));
+    }
+
+    #[test]
+    fn boundary() {
+        assert_ne!(hash_tagged(b"ab", b"c", 0), hash_tagged(b"a", b"bc", 0));
+        assert_ne!(hash_tagged(b"abc", b"", 0), hash_tagged(b"", b"abc", 0));
+    }
+
+    #[test]
+    fn framing() {
+        let mut framed = [0; 8 + 2 + 4];
+        framed[..8].copy_from_slice(&2u64.to_le_bytes());
+        framed[8..10].copy_from_slice(b"v1");
+        framed[10..].copy_from_slice(b"data");
+
+        assert_eq!(hash_tagged(b"v1", b"data", 7), hash_seeded(&framed, 7));
+    }
+
+    #[test]
+    fn versioned() {
+        let data = b"to be or not to be";
+
+        let digests = [
+            hash_versioned(0, data, 7),
+            hash_versioned(1, data, 7),
+            hash_versioned(255, data, 7),
+        ];
+        assert_ne!(digests[0], digests[1]);
+        assert_ne!(digests[0], digests[2]);
+        assert_ne!(digests[1], digests[2]);
+        for &digest in &digests {
+            assert_ne!(digest, hash_seeded(data, 7));
+        }
+
+        let mut framed = [0; 1 + 18];
+        framed[0] = 255;
+        framed[1..].copy_from_slice(data);
+        assert_eq!(hash_versioned(255, data, 7), hash_seeded(&framed, 7));
+        assert_eq!(hash_versioned(1, b"", 7), hash_seeded(&[1], 7));
+    }
+
+    #[test]
+    fn pair() {
+        assert_ne!(hash_pair_seeded(b"ab", b"c", 0), hash_pair_seeded(b"a", b"bc", 0));
+        assert_ne!(hash_pair_seeded(b"abc", b"", 0), hash_pair_seeded(b"", b"abc", 0));
+        assert_ne!(hash_pair_seeded(b"a", b"b", 0), hash_pair_seeded(b"b", b"a", 0));
+        assert_ne!(hash_pair_seeded(b"a", b"b", 0), hash_pair_seeded(b"a", b"b", 1));
+        assert_ne!(hash_pair_seeded(b"", b"", 0), hash_seeded(b"", 0));
+    }
+
+    #[test]
+    fn pair_framing() {
+        let mut framed = [0; 8 + 3 + 8 + 5];
+        framed[..8].copy_from_slice(&3u64.to_le_bytes());
+        framed[8..11].copy_from_slice(b"key");
+        framed[11..19].copy_from_slice(&5u64.to_le_bytes());
+        framed[19..].copy_from_slice(b"value");
+
+        assert_eq!(hash_pair_seeded(b"key", b"value", 7), hash_seeded(&framed, 7));
+    }
+
+    #[test]
+    fn framed2() {
+        let record = b"HDR1to be or not to be";
+
+        for split in 0..record.len() {
+            let (header, body) = record.split_at(split);
+            assert_eq!(hash_framed2(header, body, 7), hash_pair_seeded(header, body, 7));
+
+            // Shifting a byte from the header to the body, or the other way around.
+            let (shorter, longer) = record.split_at(split + 1);
+            assert_ne!(hash_framed2(header, body, 7), hash_framed2(shorter, longer, 7));
+        }
+
+        // The header comes first.
+        let mut hasher = SeaHasher::with_seed(7);
+        hasher.write_u64(4);
+        hasher.write(b"HDR1");
+        hasher.write_u64(18);
+        hasher.write(b"to be or not to be");
+        assert_eq!(hash_framed2(b"HDR1", b"to be or not to be", 7), hasher.finish());
+        assert_ne!(hash_framed2(b"HDR1", b"body", 7), hash_framed2(b"body", b"HDR1", 7));
+    }
+}
@@ -0,0 +1,307 @@
+//! Adapters for hashing data as it passes through `std::io`.
+
+use std::cmp;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::ops::Deref;
+use std::path::Path;
+use core::hash::Hasher;
+
+use SeaHasher;
+
+/// A reader which hashes the bytes read through it.
+///
+/// This wraps some other reader and forwards reads to it, while updating a `SeaHasher` with the
+/// bytes which were actually read. The digest is the same as the one of `hash_seeded()` over the
+/// bytes read so far.
+pub struct HashReader<R> {
+    /// The inner reader.
+    inner: R,
+    /// The hash state of the bytes read so far.
+    hasher: SeaHasher,
+}
+
+impl<R: Read> HashReader<R> {
+    /// Wrap a reader, hashing with the default seed (matching `hash()`).
+    pub fn new(inner: R) -> HashReader<R> {
+        HashReader {
+            inner,
+            hasher: SeaHasher::new(),
+        }
+    }
+
+    /// Wrap a reader, hashing with some seed (matching `hash_seeded()`).
+    pub fn with_seed(inner: R, seed: u64) -> HashReader<R> {
+        HashReader {
+            inner,
+            hasher: SeaHasher::with_seed(seed),
+        }
+    }
+
+    /// Get the digest of the bytes read so far.
+    pub fn hash(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    /// Get the inner reader back together with the digest of the bytes read.
+    pub fn finish(self) -> (R, u64) {
+        let hash = self.hasher.finish();
+        (self.inner, hash)
+    }
+}
+
+impl<R: Read> Read for HashReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        // Only the bytes which were filled in are part of the stream.
+        self.hasher.write(&buf[..n]);
+
+        Ok(n)
+    }
+}
+
+/// Hash everything read from some reader according to a chosen seed.
+///
+/// This reads until the end of the reader, and gives the same digest as `hash_seeded()` over the
+/// bytes read.
+pub fn hash_reader<R: Read>(reader: R, seed: u64) -> io::Result<u64> {
+    let mut reader = HashReader::with_seed(reader, seed);
+    io::copy(&mut reader, &mut io::sink())?;
+
+    Ok(reader.hash())
+}
+
+/// Hash everything read from a sequence of readers according to a chosen seed.
+///
+/// The readers are read until their end one after another, through one hasher, so the digest is
+/// the same as `hash_seeded()` over the concatenation of their bytes (e.g. of the parts of a file
+/// stored separately), without joining them. The first error is returned as is, and the readers
+/// after it are not read.
+pub fn hash_readers<I: IntoIterator<Item = R>, R: Read>(readers: I, seed: u64) -> io::Result<u64> {
+    let mut hasher = SeaHasher::with_seed(seed);
+    for reader in readers {
+        let mut reader = HashReader {
+            inner: reader,
+            hasher,
+        };
+        io::copy(&mut reader, &mut io::sink())?;
+        hasher = reader.hasher;
+    }
+
+    Ok(hasher.finish())
+}
+
+/// Copy everything from some reader to some writer, hashing the bytes along the way.
+///
+/// This is `io::copy()` through a `HashReader`: it returns the digest of the copied bytes (the same
+/// as `hash_seeded()` over them) together with their number. On error, the bytes copied so far
+/// have reached the writer, but the digest is lost.
+pub fn copy_hashed<R: Read, W: Write>(reader: R, mut writer: W, seed: u64) -> io::Result<(u64, u64)> {
+    let mut reader = HashReader::with_seed(reader, seed);
+    let copied = io::copy(&mut reader, &mut writer)?;
+
+    Ok((reader.hash(), copied))
+}
+
+/// The largest buffer used by `hash_reader_sized()`.
+///
+/// This is a multiple of 32 bytes, so as long as the reader fills the buffer on every read, each
+/// chunk but the last goes through the block loop of the hasher as a whole, without any words
+/// left over.
+const SIZED_BUF_LEN: usize = 1 << 16;
+
+/// Hash everything read from some reader of known length according to a chosen seed.
+///
+/// This is like `hash_reader()`, but the reader is expected to yield exactly `len` bytes (e.g.
+/// the size of a file from its metadata). The hint is used to size the read buffer (so small
+/// inputs don't allocate a big one), and the length is validated at the end: if the reader ends
+/// early, an error of kind `UnexpectedEof` is returned, and if it yields more than `len` bytes, an
+/// error of kind `InvalidData` is returned. Otherwise, the digest is the same as `hash_seeded()`
+/// over the bytes read.
+pub fn hash_reader_sized<R: Read>(reader: R, len: u64, seed: u64) -> io::Result<u64> {
+    let mut reader = HashReader::with_seed(reader, seed);
+    let mut buf = vec![0; cmp::min(len, SIZED_BUF_LEN as u64) as usize];
+
+    let mut left = len;
+    while left > 0 {
+        let n = cmp::min(left, buf.len() as u64) as usize;
+        reader.read_exact(&mut buf[..n])?;
+        left -= n as u64;
+    }
+
+    // Check that the reader ends here, without hashing the excess.
+    if io::copy(&mut (&mut reader.inner).take(1), &mut io::sink())? != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The reader is longer than expected."));
+    }
+
+    Ok(reader.hash())
+}
+
+/// Hash the concatenation of some vectored buffers according to a chosen seed.
+///
+/// This is `hash_seeded()` over the bytes of all the slices joined together (e.g. as received by
+/// `read_vectored()`), without copying them: partial words are carried over from one slice to
+/// the next. Empty slices are allowed and contribute nothing. Both `IoSlice` and `IoSliceMut` (or
+/// any other slices of bytes) are accepted.
+pub fn hash_io_slices<S: Deref<Target = [u8]>>(slices: &[S], seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    for slice in slices {
+        hasher.write(slice);
+    }
+
+    hasher.finish()
+}
+
+/// Check that the content of some file has a given digest.
+///
+/// This streams the file through `hash_reader()` and compares the result against `expected`,
+/// returning whether they match. Errors opening or reading the file are returned as is.
+pub fn verify_file<P: AsRef<Path>>(path: P, seed: u64, expected: u64) -> io::Result<bool> {
+    let file = File::open(path)?;
+
+    Ok(hash_reader(file, seed)? == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Cursor, IoSlice, IoSliceMut};
+
+    use {hash, hash_seeded};
+
+    #[test]
+    fn small_chunks() {
+        let data: Vec<u8> = (0..1000).map(|x| (x * 13) as u8).collect();
+        let mut reader = HashReader::with_seed(Cursor::new(&data[..]), 42);
+
+        let mut buf = [0; 5];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+        }
+
+        let (_, digest) = reader.finish();
+        assert_eq!(digest, hash_seeded(&data, 42));
+    }
+
+    #[test]
+    fn partial() {
+        let data = b"to be or not to be";
+        let mut reader = HashReader::new(Cursor::new(&data[..]));
+
+        let mut buf = [0; 7];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.hash(), hash(&data[..7]));
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(reader.hash(), hash(data));
+    }
+
+    #[test]
+    fn reader() {
+        let data: Vec<u8> = (0..100000).map(|x| (x * 7) as u8).collect();
+
+        assert_eq!(hash_reader(&data[..], 42).unwrap(), hash_seeded(&data, 42));
+        assert_eq!(hash_reader(io::empty(), 42).unwrap(), hash_seeded(&[], 42));
+    }
+
+    #[test]
+    fn readers() {
+        let data: Vec<u8> = (0..1000).map(|x| (x * 13) as u8).collect();
+        let parts = [Cursor::new(&data[..3]), Cursor::new(&data[3..517]), Cursor::new(&data[517..])];
+
+        assert_eq!(hash_readers(parts.iter().cloned(), 42).unwrap(), hash_seeded(&data, 42));
+        assert_eq!(hash_readers(vec![io::empty(), io::empty()], 42).unwrap(), hash_seeded(&[], 42));
+        assert_eq!(hash_readers(Vec::<io::Empty>::new(), 42).unwrap(), hash_seeded(&[], 42));
+    }
+
+    #[test]
+    fn copy() {
+        let data: Vec<u8> = (0..100000).map(|x| (x * 11) as u8).collect();
+
+        let mut out = Cursor::new(Vec::new());
+        let (digest, copied) = copy_hashed(Cursor::new(&data[..]), &mut out, 42).unwrap();
+        assert_eq!(digest, hash_seeded(&data, 42));
+        assert_eq!(copied, 100000);
+        assert_eq!(out.into_inner(), data);
+
+        let mut out = Vec::new();
+        assert_eq!(copy_hashed(io::empty(), &mut out, 42).unwrap(), (hash_seeded(&[], 42), 0));
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn sized() {
+        let data: Vec<u8> = (0..100000).map(|x| (x * 7) as u8).collect();
+
+        assert_eq!(hash_reader_sized(&data[..], 100000, 42).unwrap(), hash_seeded(&data, 42));
+        assert_eq!(hash_reader_sized(&data[..5], 5, 42).unwrap(), hash_seeded(&data[..5], 42));
+        assert_eq!(hash_reader_sized(io::empty(), 0, 42).unwrap(), hash_seeded(&[], 42));
+    }
+
+    #[test]
+    fn sized_short() {
+        let data = [1; 100];
+
+        let err = hash_reader_sized(&data[..], 101, 42).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn sized_long() {
+        let data = [1; 100];
+
+        let err = hash_reader_sized(&data[..], 99, 42).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = hash_reader_sized(&data[..], 0, 42).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn io_slices() {
+        let data: Vec<u8> = (0..200).map(|x| (x * 7) as u8).collect();
+        let slices = [
+            IoSlice::new(&data[..3]),
+            IoSlice::new(&[]),
+            IoSlice::new(&data[3..20]),
+            IoSlice::new(&data[20..21]),
+            IoSlice::new(&data[21..150]),
+            IoSlice::new(&[]),
+            IoSlice::new(&data[150..]),
+        ];
+
+        assert_eq!(hash_io_slices(&slices, 42), hash_seeded(&data, 42));
+        assert_eq!(hash_io_slices(&slices[..2], 42), hash_seeded(&data[..3], 42));
+        let empty: [IoSlice; 0] = [];
+        assert_eq!(hash_io_slices(&empty, 42), hash_seeded(&[], 42));
+        assert_eq!(hash_io_slices(&[IoSlice::new(&[])], 42), hash_seeded(&[], 42));
+
+        let mut data = data;
+        let (a, b) = data.split_at_mut(77);
+        let slices = [IoSliceMut::new(a), IoSliceMut::new(b)];
+        assert_eq!(hash_io_slices(&slices, 42), hash_seeded(&data, 42));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn file() {
+        use std::{env, fs, process};
+
+        let data = b"love is a wonderful terrible thing";
+        let path = env::temp_dir().join(format!("seahash-verify-{}", process::id()));
+        fs::write(&path, data).unwrap();
+
+        let correct = verify_file(&path, 42, hash_seeded(data, 42));
+        let wrong = verify_file(&path, 42, hash_seeded(data, 43));
+        let missing = verify_file(path.with_extension("missing"), 42, 0);
+        fs::remove_file(&path).unwrap();
+
+        assert!(correct.unwrap());
+        assert!(!wrong.unwrap());
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
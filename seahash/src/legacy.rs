@@ -0,0 +1,216 @@
+//! Reproduction of outputs of older versions.
+//!
+//! Version 3.0.0 changed the output of the crate in two ways, both of which are reproduced here,
+//! so digests stored by programs built against older versions can still be verified.
+//!
+//! Up to and including version 2.1.1, the optimized implementation read every full 64-bit word
+//! wrongly on 32-bit targets: the lower 32 bits of the word were read twice, so the upper half of
+//! every 8-byte word was replaced by a copy of the lower half. Only the bytes of a trailing partial
+//! word (less than 8 bytes) were read correctly. As such, `hash()` and `hash_seeded()` gave digests
+//! deviating from the specification (and from 64-bit targets) for any input of 8 bytes or more.
+//! This was fixed in version 3.0.0. `hash_legacy()` and `hash_seeded_legacy()` reproduce the old
+//! 32-bit output on any target. On 64-bit targets, the output of `hash()` and `hash_seeded()`
+//! never changed, so digests stored there are valid as is.
+//!
+//! Version 3.0.0 also made `SeaHasher` a streaming hasher, changing all of its digests on every
+//! target. The old hasher chained its writes instead: every write was hashed on its own, and the
+//! result mixed into a single state word. `SeaHasherLegacy` reproduces it.
+//!
+//! Neither is a revision of the algorithm (and `ALGORITHM_VERSION` is unaffected): the old 32-bit
+//! output never matched the specification, and the old hasher predates the versioning.
+
+use core::hash::Hasher;
+
+use {diffuse, hash_seeded};
+use consts::{DEFAULT_SEED, INIT_B, INIT_C, INIT_D, LEGACY_HASHER_SEED, LEGACY_HASHER_STATE};
+
+/// Read a (possibly partial) word the way the old 32-bit implementation did.
+fn read_word(word: &[u8]) -> u64 {
+    let mut bytes = [0; 8];
+    bytes[..word.len()].copy_from_slice(word);
+
+    if word.len() == 8 {
+        // Full words got their lower half duplicated.
+        bytes.copy_within(..4, 4);
+    }
+
+    u64::from_le_bytes(bytes)
+}
+
+/// Hash some buffer like `hash()` did on 32-bit targets up to version 2.1.1.
+///
+/// This is only meant for verifying digests stored by such programs. New digests should be made
+/// with `hash()`.
+pub fn hash_legacy(buf: &[u8]) -> u64 {
+    hash_seeded_legacy(buf, DEFAULT_SEED)
+}
+
+/// Hash some buffer like `hash_seeded()` did on 32-bit targets up to version 2.1.1.
+///
+/// This is only meant for verifying digests stored by such programs. New digests should be made
+/// with `hash_seeded()`.
+pub fn hash_seeded_legacy(buf: &[u8], seed: u64) -> u64 {
+    let mut state = [seed, INIT_B, INIT_C, INIT_D];
+    for (i, word) in buf.chunks(8).enumerate() {
+        state[i % 4] = diffuse(state[i % 4] ^ read_word(word));
+    }
+
+    diffuse(state[0] ^ state[1] ^ state[2] ^ state[3] ^ buf.len() as u64)
+}
+
+/// The `SeaHasher` of versions up to 2.1.1.
+///
+/// Every `write()` XORs the digest of the written bytes (by `hash_seeded()` with the seed of the
+/// hasher) into the state, which is then diffused, and integers are XOR'd into the state directly.
+/// As such, the digest depends on how the input is split into writes, and it does not match any
+/// one-shot function. This is only meant for verifying digests stored by such programs (e.g. of
+/// types hashed through `Hash`). New digests should be made with `SeaHasher`.
+///
+/// This reproduces the output of 64-bit targets. On 32-bit targets, the old hasher also inherited
+/// the wrong word reads of `hash_seeded()` for writes of 8 bytes or more, which is not reproduced.
+#[derive(Clone, Copy)]
+pub struct SeaHasherLegacy {
+    /// The state of the hasher.
+    state: u64,
+    /// The seed of the hasher.
+    seed: u64,
+}
+
+impl Default for SeaHasherLegacy {
+    fn default() -> SeaHasherLegacy {
+        SeaHasherLegacy::with_seed(LEGACY_HASHER_SEED)
+    }
+}
+
+impl SeaHasherLegacy {
+    /// Create a new `SeaHasherLegacy` with default state, like the old `SeaHasher::new()`.
+    pub fn new() -> SeaHasherLegacy {
+        SeaHasherLegacy::default()
+    }
+
+    /// Construct a new `SeaHasherLegacy` given some seed, like the old `SeaHasher::with_seed()`.
+    pub fn with_seed(seed: u64) -> SeaHasherLegacy {
+        SeaHasherLegacy {
+            state: LEGACY_HASHER_STATE,
+            seed,
+        }
+    }
+}
+
+impl Hasher for SeaHasherLegacy {
+    fn finish(&self) -> u64 {
+        diffuse(self.state)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.state = diffuse(self.state ^ hash_seeded(bytes, self.seed));
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.state = diffuse(self.state ^ n);
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use {hash, SeaHasher};
+
+    #[test]
+    fn recorded() {
+        // These were recorded from version 2.1.1 with the 32-bit word reads.
+        assert_eq!(hash_legacy(b"to be or not to be"), 9016439325713423398);
+        assert_eq!(hash_legacy(b"abc"), 15303965242653563474);
+        assert_eq!(hash_seeded_legacy(b"love is a wonderful terrible thing", 42), 3721645853513539226);
+
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7 + 3) as u8;
+        }
+
+        let recorded = [
+            (0, 5108996110531062742),
+            (1, 2712418955505321299),
+            (7, 2785076776313681533),
+            (8, 1747465791484168243),
+            (9, 13150391205100320605),
+            (15, 6118261047988952702),
+            (16, 8189992941974459312),
+            (17, 10010168885390390545),
+            (24, 2399988889846693007),
+            (31, 11657312064508469932),
+            (32, 8656515330521101263),
+            (33, 10246833745743362333),
+            (63, 914513470940965296),
+            (64, 13290139754629720410),
+            (100, 8060913871129937718),
+            (255, 5354839946190861766),
+            (300, 4639963199365335073),
+        ];
+        for &(n, digest) in recorded.iter() {
+            assert_eq!(hash_seeded_legacy(&buf[..n], 1), digest, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn short() {
+        // Inputs without a full word were never affected.
+        for n in 0..8 {
+            let buf = [0xAB; 7];
+            assert_eq!(hash_seeded_legacy(&buf[..n], 1), hash_seeded(&buf[..n], 1));
+        }
+
+        assert_ne!(hash_legacy(b"to be or not to be"), hash(b"to be or not to be"));
+    }
+
+    #[test]
+    fn hasher_recorded() {
+        // These were recorded from the `SeaHasher` of version 2.1.1 on x86_64.
+        let mut h = SeaHasherLegacy::new();
+        h.write(b"to be or not to be");
+        assert_eq!(h.finish(), 73883327605709133);
+        assert_eq!(SeaHasherLegacy::new().finish(), 12978030409192471457);
+
+        let mut h = SeaHasherLegacy::new();
+        h.write(&[]);
+        assert_eq!(h.finish(), 14882108968376589914);
+
+        let mut h = SeaHasherLegacy::new();
+        h.write_u8(1);
+        h.write_u16(2);
+        h.write_u32(3);
+        h.write_u64(4);
+        h.write_usize(5);
+        assert_eq!(h.finish(), 4521546360684225767);
+
+        // The writes are chained, so splitting them changes the digest.
+        let mut h = SeaHasherLegacy::with_seed(42);
+        h.write(b"love is a wonderful terrible thing");
+        assert_eq!(h.finish(), 11525622525925486585);
+        let mut h = SeaHasherLegacy::with_seed(42);
+        h.write(b"love is a ");
+        h.write(b"wonderful terrible thing");
+        assert_eq!(h.finish(), 10958565219910300173);
+
+        let mut current = SeaHasher::new();
+        current.write(b"to be or not to be");
+        assert_ne!(current.finish(), 73883327605709133);
+    }
+}
@@ -51,6 +51,18 @@
 //! This is **not** a cryptographic function, and it certainly should not be used as one. If you
 //! want a good cryptograhic hash function, you should use SHA-3 (Keccak) or BLAKE2.
 //!
+//! # Panics
+//!
+//! The core hashing functions (`hash()`, `hash_seeded()`, the `reference` module and the `Hasher`
+//! implementation of `SeaHasher`) never panic, whatever the input and whatever the build profile.
+//! All arithmetic is explicitly wrapping (enforced by `clippy::arithmetic_side_effects` in the
+//! modules implementing them, `buffer`, `stream` and `reference`), so overflow checks do not
+//! apply, and every index is bounded by construction. In particular, the length counter of a
+//! hasher silently wraps after 2^64 bytes, exactly like the length folded into the digest does.
+//!
+//! The one exception is the `debug-crosscheck` feature: in debug builds, `hash_seeded()` (and thus
+//! `hash()`) then deliberately panics if its digest diverges from the reference implementation.
+//!
 //! # Statistical guarantees
 //!
 //! SeaHash comes with certain proven guarantees about the statistical properties of the output:
@@ -120,41 +132,125 @@
 //!
 //! See the [`reference`](./reference) module.
 
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(hasher_prefixfree_extras))]
 #![warn(missing_docs)]
 
-pub use buffer::{hash, hash_seeded};
-pub use stream::SeaHasher;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "hashbrown")]
+extern crate hashbrown;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate bincode;
+#[cfg(feature = "self-test-on-load")]
+extern crate ctor;
+#[cfg(feature = "bytes")]
+extern crate bytes;
+#[cfg(all(test, feature = "std"))]
+extern crate dashmap;
+
+pub use buffer::{hash, hash_seeded, hash_raw, hash_fast, hash_boosted, hash_multi, hash_raw_parts,
+                 hash_maybe_uninit, hash_ref, hash_ring, hash_u64_array,
+                 hash_zero_sensitive, trailing_zero_sensitive, hash128, hash_multi_width,
+                 hash_zeros, hash_compare, hash_small};
+pub use consts::{ALGORITHM_VERSION, ALGORITHM_NAME};
+pub use stream::{SeaHasher, SeaHasherRaw, HashSnapshot};
+pub use seed::{seed_from_str, derive_seeds, is_trivial_seed};
+pub use map::SeaRandomState;
+pub use digest::{SeaDigest, Hash64, hash_wrapped, OrderedHash};
+pub use chunked::{hash_with_cancel, hash_partial, PartialHash, hash_strided, hash_dyn_iter,
+                  hash_generated, hash_chunks_with_progress, hash_soa};
+pub use output::{hash_nonzero, bucket, hash_le_bytes, hash_with_tag, hash_bounded, TooLarge,
+                 hash_unit_f64, hash_checked, hash_i64, hash32, hash_utf8};
+pub use combine::{combine_many, set_digest, ChunkVerifier, ChunkMismatch};
+pub use profile::hash_profiled;
+pub use framing::{hash_tagged, hash_pair_seeded, hash_versioned, hash_framed2};
+pub use selftest::self_test;
+pub use fortified::hash_fortified;
+pub use mixer::{Mixer, SeaMixer, hash_with_mixer};
+pub use window::WindowHash;
+#[cfg(feature = "alloc")]
+pub use chunked::hash_records;
+#[cfg(feature = "hashbrown")]
+pub use map::{SeaHashMap, SeaHashSet};
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::{birthday_collisions, explain, Explanation, Step};
+#[cfg(feature = "serde")]
+pub use structured::hash_serialize;
+#[cfg(feature = "bytes")]
+pub use segmented::{hash_bytes_crate, hash_buf};
+#[cfg(feature = "legacy")]
+pub use legacy::{hash_legacy, hash_seeded_legacy, SeaHasherLegacy};
+#[cfg(feature = "std")]
+pub use map::SeaMapExt;
+#[cfg(feature = "std")]
+pub use io::{HashReader, hash_reader, hash_reader_sized, verify_file, hash_io_slices,
+             copy_hashed, hash_readers};
+#[cfg(feature = "std")]
+pub use ffi::{hash_cstr, hash_osstr};
+#[cfg(feature = "random")]
+pub use map::RehashableState;
 
 pub mod reference;
+mod consts;
 mod buffer;
 mod stream;
+mod seed;
+mod map;
+mod digest;
+mod chunked;
+mod output;
+mod combine;
+mod framing;
+mod profile;
+mod selftest;
+mod fortified;
+mod mixer;
+mod window;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod ffi;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+#[cfg(feature = "serde")]
+mod structured;
+#[cfg(feature = "bytes")]
+mod segmented;
+#[cfg(feature = "legacy")]
+mod legacy;
 
 /// The diffusion function.
 ///
 /// This is a bijective function emitting chaotic behavior. Such functions are used as building
 /// blocks for hash functions.
-fn diffuse(mut x: u64) -> u64 {
+///
+/// This is exposed such that the output of `hash_raw()` can be finalized by hand.
+pub fn diffuse(mut x: u64) -> u64 {
     // Move entropy up by scattering through multiplication.
-    x = x.wrapping_mul(0x7ed0e9fa0d94a33);
+    x = x.wrapping_mul(consts::P);
     // We still need more entropy downwards. Flipping higher bits won't flip lower ones, so far.
     // For example, if you flip the most significant bit, the 32'th bit will flip per the XOR-shift
     // subdiffusion, but this flip will only be scattered by the multiplication to flipping bits
     // higher than the 32'th, meaning that the ones lower will be unaffected. As such, we need to
     // get some entropy down.
-    x = x ^ (x >> 32);
+    x ^= x >> 32;
     // So far, the avalanche diagram looks pretty good, but it still emits stripe patterns. For
     // example, flipping the 5'th lowest bit won't flip the least significant bit because of the
     // choice of scalar (in particular, observe how it leaves the 32'th bit unflipped after the
     // multiplication, which means that the XOR-shift never affects the lowest bit). No choice of
     // scalar will make this go away, it will merely change the unaffected bits. Instead, we need
     // to make the behavior more undeterministic by scattering bits through multiplication.
-    x = x.wrapping_mul(0x7ed0e9fa0d94a33);
+    x = x.wrapping_mul(consts::P);
     // This is the final stage of the diffusion function. There are still issues with the lowest
     // bits, which are still unaffected by the multiplication above. However, the multiplication
     // solved the higher bits' dependence, so lending entropy from the higher half will fix the
     // issues with the lower half.
-    x = x ^ (x >> 32);
+    x ^= x >> 32;
 
     // There is still a bias, but this is solved in the very last round of the hash function,
     // because applying this function twice, reduces this bias.
@@ -162,6 +258,65 @@ fn diffuse(mut x: u64) -> u64 {
     x
 }
 
+/// The inverse of the diffusion function.
+///
+/// `undiffuse(diffuse(x)) == x` (and vice versa) for every `x`. This undoes each step of
+/// `diffuse()` in reverse order: the XOR-shift by 32 is its own inverse, and the multiplication is
+/// inverted by multiplying with the inverse of the multiplier modulo 2^64. It allows e.g. reverting
+/// blocks mixed into a lane (see `SeaHasher::unwrite_u64()`).
+pub fn undiffuse(mut x: u64) -> u64 {
+    x ^= x >> 32;
+    x = x.wrapping_mul(consts::P_INV);
+    x ^= x >> 32;
+    x = x.wrapping_mul(consts::P_INV);
+
+    x
+}
+
+/// Mix a 64-bit block into a lane (component of the state vector).
+///
+/// This is `diffuse(lane ^ block)`, i.e. the update every block of the input goes through.
+#[inline]
+pub fn mix_block(lane: u64, block: u64) -> u64 {
+    diffuse(lane ^ block)
+}
+
+/// Finalize the lanes (the state vector) into the digest.
+///
+/// This XORs the four lanes together with the length of the input (in bytes), and diffuses the
+/// result.
+#[inline]
+pub fn finalize(lanes: [u64; 4], total_len: u64) -> u64 {
+    diffuse(lanes[0] ^ lanes[1] ^ lanes[2] ^ lanes[3] ^ total_len)
+}
+
+/// Finalize the lanes into the upper word of the 128-bit digest (see `hash128()`).
+///
+/// Contrary to `finalize()`, this chains the lanes through the diffusion function one after
+/// another, so it is not just a function of their XOR, and inputs whose lower words collide
+/// generally still differ here.
+#[inline]
+fn finalize_high(lanes: [u64; 4], total_len: u64) -> u64 {
+    let mut x = diffuse(lanes[0] ^ total_len);
+    x = diffuse(x ^ lanes[1]);
+    x = diffuse(x ^ lanes[2]);
+
+    diffuse(x ^ lanes[3])
+}
+
+/// The fast, single-round diffusion function.
+///
+/// This is the first half of `diffuse()`, i.e. one multiplication followed by one XOR-shift. It
+/// is still bijective, but the avalanche is considerably worse: the lower bits of the output only
+/// depend on the lower bits of the input (and the upper half's contribution through the shift).
+/// It is used by `hash_fast()`.
+fn diffuse_fast(mut x: u64) -> u64 {
+    x = x.wrapping_mul(consts::P);
+    x ^= x >> 32;
+
+    x
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +330,93 @@ mod tests {
         assert_eq!(diffuse(2), 10477652027101941690);
         assert_eq!(diffuse(3), 7825972970944271313);
     }
+
+    #[test]
+    fn undiffuse_inverts() {
+        for &x in &[0, 1, 2, 0xDEADBEEF, 94203824938, !0, 1 << 63] {
+            assert_eq!(undiffuse(diffuse(x)), x);
+            assert_eq!(diffuse(undiffuse(x)), x);
+        }
+        assert_eq!(undiffuse(10193074813231793594), 94203824938);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn fixed_points() {
+        // Zero is a fixed point of the diffusion function (it only multiplies and XOR-shifts).
+        assert_eq!(diffuse(0), 0);
+
+        // Check that there are no other fixed points or short cycles among a sample of inputs,
+        // which would hint at a broken multiplier: small integers, single bits, and scattered
+        // values.
+        let mut x = 1u64;
+        let sample = (1..10000).chain((0..64).map(|i| 1 << i)).chain((0..10000).map(|_| {
+            x = diffuse(x.wrapping_add(consts::INIT_B));
+            x
+        }));
+        for x in sample {
+            let mut y = x;
+            for _ in 0..16 {
+                y = diffuse(y);
+                assert_ne!(y, x);
+            }
+        }
+    }
+
+    /// Count, for every pair of input bit and output bit, how often flipping the input bit flips
+    /// the output bit, and return the average deviation from 1/2.
+    fn avalanche_bias<F: Fn(u64) -> u64>(f: F) -> f64 {
+        const SAMPLES: u64 = 2000;
+
+        let mut flips = [[0u32; 64]; 64];
+        for n in 0..SAMPLES {
+            let x = diffuse(n);
+            for (i, row) in flips.iter_mut().enumerate() {
+                let diff = f(x) ^ f(x ^ (1 << i));
+                for (j, count) in row.iter_mut().enumerate() {
+                    *count += (diff >> j) as u32 & 1;
+                }
+            }
+        }
+
+        let total: f64 = flips.iter().flat_map(|row| row.iter())
+            .map(|&count| (count as f64 / SAMPLES as f64 - 0.5).abs())
+            .sum();
+
+        total / (64 * 64) as f64
+    }
+
+    #[test]
+    fn composition() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..buf.len() {
+            let mut lanes = [consts::DEFAULT_SEED, consts::INIT_B, consts::INIT_C, consts::INIT_D];
+            for (i, chunk) in buf[..n].chunks(8).enumerate() {
+                // Pad the last chunk with zeros.
+                let mut block = [0; 8];
+                block[..chunk.len()].copy_from_slice(chunk);
+                lanes[i % 4] = mix_block(lanes[i % 4], u64::from_le_bytes(block));
+            }
+
+            assert_eq!(finalize(lanes, n as u64), reference::hash(&buf[..n]));
+            assert_eq!(finalize(lanes, n as u64), hash(&buf[..n]));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn avalanche() {
+        // With 2000 samples, an ideal function has an average bias of about 0.009 due to sampling
+        // noise alone.
+
+        // The single-round diffusion is far from the avalanche criterion (about 0.28): e.g.
+        // flipping the top input bit flips exactly two output bits.
+        assert!(avalanche_bias(diffuse_fast) > 0.25);
+        // The full diffusion is much better (about 0.017), but still a bit biased. The rest is
+        // fixed by the final diffusion of the hash function.
+        assert!(avalanche_bias(diffuse) < 0.025);
+        // Applying it twice, like the hash function does for single blocks, is close to ideal.
+        assert!(avalanche_bias(|x| diffuse(diffuse(x))) < 0.012);
+    }
 }
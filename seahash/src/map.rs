@@ -0,0 +1,340 @@
+//! Integration with hash maps.
+
+use core::hash::BuildHasher;
+#[cfg(feature = "random")]
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "random")]
+use core::mem;
+#[cfg(feature = "random")]
+use std::collections::hash_map::RandomState;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use SeaHasher;
+use consts::DEFAULT_SEED;
+
+/// A `BuildHasher` producing `SeaHasher`s.
+///
+/// This allows using SeaHash as the hash function of hash maps. Every hasher built by the same
+/// state uses the same seed, as the map requires. Note that, contrary to the standard library's
+/// `RandomState`, the default seed is fixed, so hash maps using the default state are
+/// deterministic (and thus predictable, so they should not be fed untrusted keys).
+///
+/// The state is `Copy` and `Default`, so it also satisfies the `BuildHasher + Clone` bounds of
+/// concurrent maps (e.g. `DashMap<K, V, SeaRandomState>`), where every shard clones it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeaRandomState {
+    /// The seed of the built hashers.
+    seed: u64,
+    /// The salt of the built hashers.
+    salt: u64,
+}
+
+impl Default for SeaRandomState {
+    fn default() -> SeaRandomState {
+        SeaRandomState::new()
+    }
+}
+
+impl SeaRandomState {
+    /// Create a new state with the default seed (matching `hash()`).
+    pub fn new() -> SeaRandomState {
+        SeaRandomState::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a new state given some seed (matching `hash_seeded()`).
+    pub fn with_seed(seed: u64) -> SeaRandomState {
+        SeaRandomState::with_salt(seed, 0)
+    }
+
+    /// Create a new state given some seed and a salt.
+    ///
+    /// The seed goes into the first lane of the state vector (as with `with_seed()`), while the
+    /// salt is mixed into the final fold. This allows e.g. sharded maps sharing the same seed to
+    /// spread keys over the buckets differently per shard, but keys whose full digests collide do
+    /// so under every salt. See `SeaHasher::with_seed_and_salt()`.
+    pub fn with_salt(seed: u64, salt: u64) -> SeaRandomState {
+        SeaRandomState { seed, salt }
+    }
+}
+
+impl BuildHasher for SeaRandomState {
+    type Hasher = SeaHasher;
+
+    fn build_hasher(&self) -> SeaHasher {
+        SeaHasher::with_seed_and_salt(self.seed, self.salt)
+    }
+}
+
+/// Constructors of standard hash maps using SeaHash.
+///
+/// This allows writing `HashMap::seahash()` rather than spelling out the `SeaRandomState`.
+#[cfg(feature = "std")]
+pub trait SeaMapExt {
+    /// Create an empty map with the default seed.
+    fn seahash() -> Self;
+    /// Create an empty map with some seed.
+    fn seahash_seeded(seed: u64) -> Self;
+    /// Create an empty map with the default seed and room for at least `capacity` entries.
+    fn seahash_with_capacity(capacity: usize) -> Self;
+    /// Create an empty map with some seed and room for at least `capacity` entries.
+    fn seahash_with_capacity_seeded(capacity: usize, seed: u64) -> Self;
+}
+
+#[cfg(feature = "std")]
+impl<K, V> SeaMapExt for HashMap<K, V, SeaRandomState> {
+    fn seahash() -> Self {
+        HashMap::with_hasher(SeaRandomState::new())
+    }
+
+    fn seahash_seeded(seed: u64) -> Self {
+        HashMap::with_hasher(SeaRandomState::with_seed(seed))
+    }
+
+    fn seahash_with_capacity(capacity: usize) -> Self {
+        HashMap::with_capacity_and_hasher(capacity, SeaRandomState::new())
+    }
+
+    fn seahash_with_capacity_seeded(capacity: usize, seed: u64) -> Self {
+        HashMap::with_capacity_and_hasher(capacity, SeaRandomState::with_seed(seed))
+    }
+}
+
+/// A `BuildHasher` producing `SeaHasher`s with a random seed, which can be replaced.
+///
+/// This is for long-lived maps fed with semi-trusted keys. If the owner of the map notices that
+/// the keys collide excessively (e.g. because lookups get slow, or a bucket count of its own
+/// grows too large), it can call `rehash_map()` (or `rotate_seed()` followed by rebuilding the map
+/// by hand), which moves every entry to a map using a fresh random seed, so a set of keys crafted
+/// against the old seed no longer collides.
+///
+/// The seeds are drawn from the randomness of the standard library (the one behind
+/// `RandomState`).
+#[cfg(feature = "random")]
+#[derive(Clone, Debug)]
+pub struct RehashableState {
+    /// The state with the current seed.
+    state: SeaRandomState,
+}
+
+#[cfg(feature = "random")]
+impl Default for RehashableState {
+    fn default() -> RehashableState {
+        RehashableState::new()
+    }
+}
+
+#[cfg(feature = "random")]
+impl RehashableState {
+    /// Create a new state with a random seed.
+    pub fn new() -> RehashableState {
+        RehashableState {
+            state: SeaRandomState::with_seed(random_seed()),
+        }
+    }
+
+    /// Replace the seed with a fresh random one.
+    ///
+    /// Note that this changes the hash function: a map must not be left using the state after its
+    /// seed has been rotated, but must be rebuilt (see `rehash_map()`).
+    pub fn rotate_seed(&mut self) {
+        self.state = SeaRandomState::with_seed(random_seed());
+    }
+
+    /// Get the state with the current seed.
+    pub fn state(&self) -> SeaRandomState {
+        self.state
+    }
+
+    /// Rotate the seed of some map, rehashing its entries.
+    ///
+    /// This rebuilds the map with a state with a fresh seed, moving every entry over.
+    pub fn rehash_map<K: Hash + Eq, V>(map: &mut HashMap<K, V, RehashableState>) {
+        let mut state = map.hasher().clone();
+        state.rotate_seed();
+
+        let len = map.len();
+        let old = mem::replace(map, HashMap::with_capacity_and_hasher(len, state));
+        map.extend(old);
+    }
+}
+
+#[cfg(feature = "random")]
+impl BuildHasher for RehashableState {
+    type Hasher = SeaHasher;
+
+    fn build_hasher(&self) -> SeaHasher {
+        self.state.build_hasher()
+    }
+}
+
+/// Draw a random seed.
+#[cfg(feature = "random")]
+fn random_seed() -> u64 {
+    // Every `RandomState` has distinct, randomly initialized keys.
+    RandomState::new().build_hasher().finish()
+}
+
+/// A `hashbrown` hash map using SeaHash.
+///
+/// This is usable without the standard library.
+#[cfg(feature = "hashbrown")]
+pub type SeaHashMap<K, V> = ::hashbrown::HashMap<K, V, SeaRandomState>;
+
+/// A `hashbrown` hash set using SeaHash.
+///
+/// This is usable without the standard library.
+#[cfg(feature = "hashbrown")]
+pub type SeaHashSet<T> = ::hashbrown::HashSet<T, SeaRandomState>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::hash::Hasher;
+
+    use {hash_seeded, undiffuse};
+
+    #[test]
+    fn seeded() {
+        let mut h = SeaRandomState::with_seed(500).build_hasher();
+        h.write(b"to be or not to be");
+
+        assert_eq!(h.finish(), hash_seeded(b"to be or not to be", 500));
+    }
+
+    #[test]
+    fn salted() {
+        let hash = |state: SeaRandomState| {
+            let mut h = state.build_hasher();
+            h.write(b"key");
+            h.finish()
+        };
+
+        assert_eq!(hash(SeaRandomState::with_salt(500, 0)), hash(SeaRandomState::with_seed(500)));
+        assert_ne!(hash(SeaRandomState::with_salt(500, 1)), hash(SeaRandomState::with_salt(500, 2)));
+        assert_ne!(hash(SeaRandomState::with_salt(500, 1)), hash(SeaRandomState::with_seed(500)));
+        assert_eq!(hash(SeaRandomState::with_salt(500, 1)), hash(SeaRandomState::with_salt(500, 1)));
+
+        // The salt only relabels the digests, as documented.
+        for &salt in &[1, 2, 0xDEADBEEF, !0] {
+            let salted = hash(SeaRandomState::with_salt(500, salt));
+            assert_eq!(undiffuse(salted) ^ salt, undiffuse(hash(SeaRandomState::with_seed(500))));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn map_ext() {
+        let mut map = HashMap::seahash();
+        for i in 0..1000u32 {
+            map.insert(i, i * 3);
+        }
+        for i in 0..1000 {
+            assert_eq!(map[&i], i * 3);
+        }
+        assert_eq!(*map.hasher(), SeaRandomState::new());
+
+        let map: HashMap<u32, u32, _> = HashMap::seahash_seeded(42);
+        assert_eq!(*map.hasher(), SeaRandomState::with_seed(42));
+
+        let map: HashMap<u32, u32, _> = HashMap::seahash_with_capacity(100);
+        assert!(map.capacity() >= 100);
+        assert_eq!(*map.hasher(), SeaRandomState::new());
+
+        let map: HashMap<u32, u32, _> = HashMap::seahash_with_capacity_seeded(100, 42);
+        assert!(map.capacity() >= 100);
+        assert_eq!(*map.hasher(), SeaRandomState::with_seed(42));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn dashmap() {
+        use std::sync::Arc;
+        use std::thread;
+
+        use dashmap::DashMap;
+
+        let map: Arc<DashMap<u32, u32, SeaRandomState>> =
+            Arc::new(DashMap::with_hasher(SeaRandomState::with_seed(42)));
+
+        let threads: Vec<_> = (0..4).map(|t| {
+            let map = map.clone();
+            thread::spawn(move || {
+                for i in (t..10000).step_by(4) {
+                    map.insert(i, i * 3);
+                }
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(map.len(), 10000);
+        for i in 0..10000 {
+            assert_eq!(*map.get(&i).unwrap(), i * 3);
+        }
+        assert!(map.get(&10000).is_none());
+        assert_eq!(*map.hasher(), SeaRandomState::with_seed(42));
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn rotate_seed() {
+        let hash = |state: &RehashableState, key: u64| {
+            let mut h = state.build_hasher();
+            h.write_u64(key);
+            h.finish()
+        };
+
+        let mut state = RehashableState::new();
+        let old = state.clone();
+        state.rotate_seed();
+        assert_ne!(state.state(), old.state());
+
+        // The hash function changes, but stays consistent with itself.
+        assert!((0..100).any(|key| hash(&state, key) != hash(&old, key)));
+        for key in 0..100 {
+            assert_eq!(hash(&state, key), hash(&state, key));
+            assert_eq!(hash(&state, key), hash(&state.clone(), key));
+        }
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn rehash_map() {
+        let mut map = HashMap::with_hasher(RehashableState::new());
+        for i in 0..1000u32 {
+            map.insert(i, i * 3);
+        }
+        let old = map.hasher().state();
+
+        RehashableState::rehash_map(&mut map);
+        assert_ne!(map.hasher().state(), old);
+        assert_eq!(map.len(), 1000);
+        for i in 0..1000 {
+            assert_eq!(map[&i], i * 3);
+        }
+        assert!(!map.contains_key(&1000));
+    }
+
+    #[cfg(feature = "hashbrown")]
+    #[test]
+    fn hashbrown() {
+        let mut map = SeaHashMap::default();
+        for i in 0..10000u32 {
+            map.insert(i, i * 3);
+        }
+
+        assert_eq!(map.len(), 10000);
+        for i in 0..10000 {
+            assert_eq!(map[&i], i * 3);
+        }
+        assert!(map.get(&10000).is_none());
+
+        let set: SeaHashSet<_> = (0..100u8).map(|x| [x; 40]).collect();
+        assert!(set.contains(&[42; 40]));
+        assert!(!set.contains(&[100; 40]));
+    }
+}
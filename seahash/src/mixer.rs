@@ -0,0 +1,75 @@
+//! Hashing with pluggable diffusion functions.
+
+use buffer;
+use diffuse;
+
+/// A diffusion function, i.e. the function mixing the blocks into the lanes.
+///
+/// This allows experimenting with alternative diffusion functions while keeping the rest of the
+/// construction (the lanes, the padding and the fold) as is, see `hash_with_mixer()`. For the
+/// statistical guarantees of SeaHash to carry over, the function must be bijective.
+pub trait Mixer {
+    /// Diffuse a 64-bit word.
+    fn diffuse(x: u64) -> u64;
+}
+
+/// The diffusion function of SeaHash, `diffuse()`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SeaMixer;
+
+impl Mixer for SeaMixer {
+    #[inline(always)]
+    fn diffuse(x: u64) -> u64 {
+        diffuse(x)
+    }
+}
+
+/// Hash some buffer according to a chosen seed with some diffusion function.
+///
+/// This follows the construction of `hash_seeded()`, with every use of the diffusion function
+/// (both when mixing blocks into the lanes and in the final diffusion) replaced by `M::diffuse()`.
+/// With `SeaMixer`, this is `hash_seeded()`. Any other mixer gives a distinct hash function,
+/// which is meant for research (e.g. running the avalanche tests against it), **not** for
+/// persistent digests.
+pub fn hash_with_mixer<M: Mixer>(buf: &[u8], seed: u64) -> u64 {
+    M::diffuse(buffer::hash_raw_with(buf, seed, M::diffuse))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hash_seeded;
+    use consts::{INIT_B, INIT_C, INIT_D};
+
+    /// A mixer which does not mix at all.
+    struct Identity;
+
+    impl Mixer for Identity {
+        fn diffuse(x: u64) -> u64 {
+            x
+        }
+    }
+
+    #[test]
+    fn sea_mixer() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..buf.len() {
+            assert_eq!(hash_with_mixer::<SeaMixer>(&buf[..n], 42), hash_seeded(&buf[..n], 42));
+        }
+    }
+
+    #[test]
+    fn identity() {
+        // Without diffusion, the digest is the XOR of the initial state, the blocks and the length.
+        let mut buf = [0; 20];
+        buf[..8].copy_from_slice(&1u64.to_le_bytes());
+        buf[8..16].copy_from_slice(&2u64.to_le_bytes());
+        buf[16..].copy_from_slice(&[3, 0, 0, 0]);
+
+        let expected = 42 ^ INIT_B ^ INIT_C ^ INIT_D ^ 1 ^ 2 ^ 3 ^ 20;
+        assert_eq!(hash_with_mixer::<Identity>(&buf, 42), expected);
+        assert_ne!(hash_with_mixer::<Identity>(&buf, 42), hash_seeded(&buf, 42));
+    }
+}
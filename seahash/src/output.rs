@@ -0,0 +1,326 @@
+//! Alternative forms of the digest.
+
+use core::num::NonZeroU64;
+use core::str::{self, Utf8Error};
+
+use hash_seeded;
+
+/// The value which a zero digest is mapped to by `hash_nonzero()`.
+const NONZERO_SENTINEL: u64 = 1;
+
+/// Hash some buffer according to a chosen seed, giving a nonzero digest.
+///
+/// This is `hash_seeded(buf, seed)`, except that a zero digest is mapped to `1`. That allows
+/// storing digests in a niche-optimized form (e.g. `Option<NonZeroU64>` has the size of a `u64`).
+///
+/// The mapping means that the inputs hashing to zero collide with the inputs hashing to one, i.e.
+/// the probability of a collision between two random inputs goes from 2⁻⁶⁴ to 2⁻⁶⁴ + 2⁻¹²⁷,
+/// which is negligible.
+pub fn hash_nonzero(buf: &[u8], seed: u64) -> NonZeroU64 {
+    nonzero(hash_seeded(buf, seed))
+}
+
+/// A buffer which is longer than the limit given to `hash_bounded()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooLarge {
+    /// The length of the buffer.
+    pub len: usize,
+    /// The maximal length.
+    pub max_len: usize,
+}
+
+/// Hash some buffer according to a chosen seed, refusing buffers longer than some limit.
+///
+/// If `buf` is longer than `max_len` bytes, this returns an error without reading it. Otherwise,
+/// it returns `hash_seeded(buf, seed)`. This allows bounding the work spent on untrusted input
+/// (e.g. in a protocol parser) at the hash boundary.
+pub fn hash_bounded(buf: &[u8], max_len: usize, seed: u64) -> Result<u64, TooLarge> {
+    if buf.len() > max_len {
+        return Err(TooLarge {
+            len: buf.len(),
+            max_len,
+        });
+    }
+
+    Ok(hash_seeded(buf, seed))
+}
+
+/// Hash some buffer according to a chosen seed, rejecting invalid UTF-8.
+///
+/// If `bytes` is not valid UTF-8, this returns the error of `str::from_utf8()` without hashing.
+/// Otherwise, it returns `hash_seeded(bytes, seed)`, i.e. the same digest as for the bytes of the
+/// corresponding `str`. This allows rejecting malformed text keys at the hash boundary.
+pub fn hash_utf8(bytes: &[u8], seed: u64) -> Result<u64, Utf8Error> {
+    str::from_utf8(bytes)?;
+
+    Ok(hash_seeded(bytes, seed))
+}
+
+/// Map some buffer to one of `n` buckets.
+///
+/// This hashes the buffer with `hash_seeded()`, and reduces the digest to `[0, n)` by
+/// multiplication (taking the upper 64 bits of the 128-bit product `hash * n`), rather than by
+/// modulo. This is faster, and it uses the upper bits of the digest, so (contrary to modulo of a
+/// non-power of two) the bias is bounded by `n / 2⁶⁴`, which is negligible for any sensible `n`.
+///
+/// `n` must be nonzero. This is checked in debug builds; in release builds, zero is returned.
+pub fn bucket(buf: &[u8], seed: u64, n: u64) -> u64 {
+    debug_assert!(n != 0, "The number of buckets must be nonzero.");
+
+    ((hash_seeded(buf, seed) as u128 * n as u128) >> 64) as u64
+}
+
+/// Hash some buffer according to a chosen seed, giving a uniformly distributed float in `[0, 1)`.
+///
+/// The float is the top 53 bits of the digest scaled by 2⁻⁵³, i.e. one of the 2⁵³ evenly spaced
+/// values `k / 2⁵³`. Since 53 bits is exactly the precision of an `f64`, this is exact, so there is
+/// no rounding up to `1.0` and no bias at the edges (contrary to e.g. dividing the whole digest by
+/// `u64::MAX`). This is meant for sampling, e.g. keeping a key if the result is below some rate.
+pub fn hash_unit_f64(buf: &[u8], seed: u64) -> f64 {
+    unit_f64(hash_seeded(buf, seed))
+}
+
+/// Hash some buffer according to a chosen seed, giving the digest as bytes.
+///
+/// The digest is encoded in little-endian byte order, i.e. this is equal to
+/// `hash_seeded(buf, seed).to_le_bytes()`. This is the byte order to use when storing or
+/// transmitting digests, as it doesn't depend on the platform.
+pub fn hash_le_bytes(buf: &[u8], seed: u64) -> [u8; 8] {
+    hash_seeded(buf, seed).to_le_bytes()
+}
+
+/// The shift extracting the tag of `hash_with_tag()` from the digest (leaving its top 7 bits).
+const TAG_SHIFT: u32 = 57;
+
+/// Hash some buffer according to a chosen seed, giving the digest and a 7-bit tag.
+///
+/// The tag is the top 7 bits of the digest, i.e. `(hash >> 57) as u8`, so it is less than 128.
+/// This is meant for the control bytes of SIMD-probed ("Swiss") tables, which keep the high bit
+/// free for the empty and deleted markers. Since the bucket index is usually taken from the low
+/// bits of the digest, the tag is independent of it.
+pub fn hash_with_tag(buf: &[u8], seed: u64) -> (u64, u8) {
+    let hash = hash_seeded(buf, seed);
+
+    (hash, (hash >> TAG_SHIFT) as u8)
+}
+
+/// Hash some buffer according to a chosen seed, giving a 32-bit digest.
+///
+/// The 64-bit digest is folded into 32 bits by XOR'ing its halves, i.e. `(h ^ (h >> 32)) as u32`,
+/// so every bit of the digest contributes. Note that plain truncation is not bad either (the final
+/// diffusion already mixes the upper half into the lower one), but folding doesn't rely on that.
+/// Both halves are uniformly distributed and the folding is balanced, so the 32-bit digest is
+/// uniformly distributed as well.
+pub fn hash32(buf: &[u8], seed: u64) -> u32 {
+    let hash = hash_seeded(buf, seed);
+
+    (hash ^ (hash >> 32)) as u32
+}
+
+/// Hash some buffer according to a chosen seed, giving a signed digest.
+///
+/// This is `hash_seeded(buf, seed) as i64`, i.e. the same 64 bits reinterpreted in two's
+/// complement (digests with the top bit set become negative), not a different value. It is meant
+/// for passing digests to languages without unsigned 64-bit integers (e.g. Java's `long`), where
+/// `digest as u64` on the way back gives the original digest.
+pub fn hash_i64(buf: &[u8], seed: u64) -> i64 {
+    hash_seeded(buf, seed) as i64
+}
+
+/// Hash some buffer according to a chosen seed, also telling whether it is empty.
+///
+/// This returns `(hash_seeded(buf, seed), buf.is_empty())`, for code where the empty input has a
+/// special meaning (e.g. a sentinel in a deserializer), so both come from one call.
+pub fn hash_checked(buf: &[u8], seed: u64) -> (u64, bool) {
+    (hash_seeded(buf, seed), buf.is_empty())
+}
+
+/// Map a digest to a float in `[0, 1)` by its top 53 bits.
+fn unit_f64(hash: u64) -> f64 {
+    (hash >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+/// Map a digest to a nonzero value.
+fn nonzero(hash: u64) -> NonZeroU64 {
+    NonZeroU64::new(hash).unwrap_or_else(|| NonZeroU64::new(NONZERO_SENTINEL).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use consts::{INIT_B, INIT_C, INIT_D};
+
+    #[test]
+    fn nonzero_digest() {
+        assert_eq!(hash_nonzero(b"to be or not to be", 1).get(), hash_seeded(b"to be or not to be", 1));
+        assert_eq!(nonzero(0).get(), NONZERO_SENTINEL);
+        assert_eq!(nonzero(42).get(), 42);
+    }
+
+    #[test]
+    fn bounded() {
+        let buf = b"to be or not to be";
+
+        assert_eq!(hash_bounded(buf, 100, 1), Ok(hash_seeded(buf, 1)));
+        assert_eq!(hash_bounded(buf, buf.len(), 1), Ok(hash_seeded(buf, 1)));
+        assert_eq!(hash_bounded(buf, buf.len() - 1, 1), Err(TooLarge { len: 18, max_len: 17 }));
+        assert_eq!(hash_bounded(&[], 0, 1), Ok(hash_seeded(&[], 1)));
+        assert_eq!(hash_bounded(buf, 0, 1), Err(TooLarge { len: 18, max_len: 0 }));
+    }
+
+    #[test]
+    fn signed() {
+        for n in 0..100u32 {
+            let buf = n.to_le_bytes();
+            assert_eq!(hash_i64(&buf, 1) as u64, hash_seeded(&buf, 1));
+            assert_eq!(hash_i64(&buf, 1) < 0, hash_seeded(&buf, 1) >> 63 == 1);
+        }
+    }
+
+    #[test]
+    fn utf8() {
+        assert_eq!(hash_utf8(b"to be or not to be", 1), Ok(hash_seeded(b"to be or not to be", 1)));
+        assert_eq!(hash_utf8("ø€𝄞".as_bytes(), 1), Ok(hash_seeded("ø€𝄞".as_bytes(), 1)));
+        assert_eq!(hash_utf8(b"", 1), Ok(hash_seeded(b"", 1)));
+
+        let err = hash_utf8(b"to be \xFF", 1).unwrap_err();
+        assert_eq!(err.valid_up_to(), 6);
+        assert_eq!(err.error_len(), Some(1));
+        // A truncated sequence at the end.
+        let err = hash_utf8(&"€".as_bytes()[..2], 1).unwrap_err();
+        assert_eq!(err.valid_up_to(), 0);
+        assert_eq!(err.error_len(), None);
+    }
+
+    #[test]
+    fn checked() {
+        assert_eq!(hash_checked(b"to be or not to be", 1), (hash_seeded(b"to be or not to be", 1), false));
+        assert_eq!(hash_checked(&[0], 1), (hash_seeded(&[0], 1), false));
+        assert_eq!(hash_checked(&[], 1), (hash_seeded(&[], 1), true));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn unit_float() {
+        assert_eq!(unit_f64(0), 0.0);
+        assert_eq!(unit_f64(1 << 63), 0.5);
+        // The largest digest stays below one.
+        assert_eq!(unit_f64(!0), 1.0 - 1.0 / (1u64 << 53) as f64);
+        assert!(unit_f64(!0) < 1.0);
+
+        const KEYS: u32 = 100000;
+        let mut sum = 0.0;
+        for key in 0..KEYS {
+            let x = hash_unit_f64(&key.to_le_bytes(), 42);
+            assert!((0.0..1.0).contains(&x));
+            sum += x;
+        }
+
+        // The standard deviation of the mean is `1 / sqrt(12 KEYS)`, i.e. about 0.0009.
+        let mean = sum / KEYS as f64;
+        assert!((mean - 0.5).abs() < 0.005, "mean = {}", mean);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn folded_32() {
+        const KEYS: u32 = 100000;
+        const BUCKETS: usize = 1024;
+
+        // Pearson's chi-squared statistic of the low bits (the bucket index of a 32-bit table).
+        let chi2 = |f: &dyn Fn(&[u8]) -> u32| {
+            let mut counts = [0u32; BUCKETS];
+            for key in 0..KEYS {
+                counts[f(&key.to_le_bytes()) as usize % BUCKETS] += 1;
+            }
+
+            let expected = KEYS as f64 / BUCKETS as f64;
+            counts.iter()
+                .map(|&c| (c as f64 - expected) * (c as f64 - expected) / expected)
+                .sum::<f64>()
+        };
+        let folded = chi2(&|buf| hash32(buf, 42));
+        let truncated = chi2(&|buf| hash_seeded(buf, 42) as u32);
+
+        // With `BUCKETS - 1` degrees of freedom, the statistic has mean `BUCKETS - 1` and variance
+        // `2(BUCKETS - 1)`, so we allow six standard deviations. Thanks to the final diffusion,
+        // truncation passes as well.
+        let df = (BUCKETS - 1) as f64;
+        assert!(folded <= df + 6.0 * (2.0 * df).sqrt(), "chi2 = {}", folded);
+        assert!(truncated <= df + 6.0 * (2.0 * df).sqrt(), "chi2 = {}", truncated);
+
+        let h = hash_seeded(b"to be or not to be", 1);
+        assert_eq!(hash32(b"to be or not to be", 1), (h as u32) ^ (h >> 32) as u32);
+    }
+
+    #[test]
+    fn le_bytes() {
+        // This module is tested without the standard library too.
+        let bytes = hash_le_bytes(b"to be or not to be", 1);
+        assert_eq!(bytes, hash_seeded(b"to be or not to be", 1).to_le_bytes());
+        assert_eq!(u64::from_le_bytes(bytes), hash_seeded(b"to be or not to be", 1));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn bucket_uniform() {
+        const KEYS: u64 = 100000;
+
+        for &n in &[1, 2, 10, 97, 1000] {
+            let mut counts = [0u32; 1000];
+            for key in 0..KEYS {
+                let b = bucket(&key.to_le_bytes(), 42, n);
+                assert!(b < n);
+                counts[b as usize] += 1;
+            }
+
+            // Pearson's chi-squared test against the uniform distribution. With `n - 1` degrees
+            // of freedom, the statistic has mean `n - 1` and variance `2(n - 1)`, so we allow six
+            // standard deviations.
+            let expected = KEYS as f64 / n as f64;
+            let chi2: f64 = counts[..n as usize].iter()
+                .map(|&c| (c as f64 - expected) * (c as f64 - expected) / expected)
+                .sum();
+            let df = (n - 1) as f64;
+
+            assert!(chi2 <= df + 6.0 * (2.0 * df).sqrt(), "n = {}, chi2 = {}", n, chi2);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn tag() {
+        const KEYS: u64 = 128 * 1000;
+
+        let mut counts = [0u32; 128];
+        for key in 0..KEYS {
+            let (hash, tag) = hash_with_tag(&key.to_le_bytes(), 42);
+            assert_eq!(hash, hash_seeded(&key.to_le_bytes(), 42));
+            assert_eq!(tag, (hash >> 57) as u8);
+            counts[tag as usize] += 1;
+        }
+
+        // Chi-squared test against the uniform distribution, as in `bucket_uniform`.
+        let expected = KEYS as f64 / 128.0;
+        let chi2: f64 = counts.iter()
+            .map(|&c| (c as f64 - expected) * (c as f64 - expected) / expected)
+            .sum();
+        let df = 127.0f64;
+
+        assert!(chi2 <= df + 6.0 * (2.0 * df).sqrt(), "chi2 = {}", chi2);
+    }
+
+    #[test]
+    fn zero_input() {
+        // Construct a seed for which some 8-byte input hashes to zero. With a single block, the
+        // folded state is `diffuse(seed ^ block) ^ b ^ c ^ d ^ 8`, and the digest is zero if and
+        // only if that is, so inverting the diffusion gives the seed.
+        let block = *b"abcdefgh";
+        let target = INIT_B ^ INIT_C ^ INIT_D ^ 8;
+        let seed = ::undiffuse(target) ^ u64::from_le_bytes(block);
+
+        assert_eq!(hash_seeded(&block, seed), 0);
+        assert_eq!(hash_nonzero(&block, seed).get(), NONZERO_SENTINEL);
+    }
+}
@@ -0,0 +1,63 @@
+//! The hash function selected by the build profile.
+//!
+//! The profile is chosen by features:
+//!
+//! - `profile-fast`: `hash_profiled()` is `hash_fast()`, which uses fewer diffusion rounds. Its
+//!   output is **not** stable across versions of this crate (the function behind the profile may
+//!   change), so it must only be used for in-memory purposes, e.g. hash maps.
+//! - Otherwise (e.g. with `profile-stable`, or neither): `hash_profiled()` is `hash_seeded()`, i.e.
+//!   the canonical SeaHash, whose output is portable and stable across versions.
+//!
+//! The features are additive: `profile-fast` wins whenever it is enabled, even together with
+//! `profile-stable`, and `profile-stable` merely requests the fallback. As features are unified
+//! across the dependency graph, any crate in a build enabling `profile-fast` switches
+//! `hash_profiled()` for all of them. As such, digests which are persisted should be made with
+//! `hash_seeded()` directly, not with `hash_profiled()`.
+
+#[cfg(not(feature = "profile-fast"))]
+use hash_seeded;
+#[cfg(feature = "profile-fast")]
+use hash_fast;
+
+/// Hash some buffer according to a chosen seed with the function of the build profile.
+///
+/// With `profile-fast`, this is some faster function with an output which may change between
+/// versions (currently `hash_fast()`), and otherwise it is `hash_seeded()`.
+#[cfg(not(feature = "profile-fast"))]
+pub fn hash_profiled(buf: &[u8], seed: u64) -> u64 {
+    hash_seeded(buf, seed)
+}
+
+/// Hash some buffer according to a chosen seed with the function of the build profile.
+///
+/// With `profile-fast`, this is some faster function with an output which may change between
+/// versions (currently `hash_fast()`), and otherwise it is `hash_seeded()`.
+#[cfg(feature = "profile-fast")]
+pub fn hash_profiled(buf: &[u8], seed: u64) -> u64 {
+    hash_fast(buf, seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "profile-fast"))]
+    #[test]
+    fn stable() {
+        // The canonical output, which persisted digests rely on.
+        assert_eq!(hash_profiled(b"to be or not to be", 0x16f11fe89b0d677c), 16114993074217697639);
+        assert_eq!(hash_profiled(b"love is a wonderful terrible thing", 42),
+                   hash_seeded(b"love is a wonderful terrible thing", 42));
+    }
+
+    #[cfg(feature = "profile-fast")]
+    #[test]
+    fn fast() {
+        use hash_seeded;
+
+        assert_eq!(hash_profiled(b"love is a wonderful terrible thing", 42),
+                   hash_fast(b"love is a wonderful terrible thing", 42));
+        // This is a different function.
+        assert_ne!(hash_profiled(b"to be or not to be", 0), hash_seeded(b"to be or not to be", 0));
+    }
+}
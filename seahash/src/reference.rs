@@ -28,8 +28,57 @@
 //!
 //! Let the final state be `(x, y, z, w)`. Then the final result is given by `H = g(x ⊕ y ⊕ z ⊕ w ⊕
 //! l)` where `l` is the number of bytes in the original buffer.
+//!
+//! # Fast variant
+//!
+//! `hash_fast` is a distinct hash function, which is defined exactly like the above, except that
+//! the blocks are mixed in by `g'(x) = h(j(x))` (i.e. only one round):
+//!
+//! ```notest
+//! d = g'(a ⊕ n)
+//! ```
+//!
+//! The final result is still given by `H = g(x ⊕ y ⊕ z ⊕ w ⊕ l)`.
+//!
+//! # Boosted variant
+//!
+//! `hash_boosted` is a distinct hash function, which is defined exactly like SeaHash, except that
+//! the `i`'th block (counting from 1) is tweaked by its position:
+//!
+//! ```notest
+//! d = g(a ⊕ n ⊕ it)
+//! ```
+//!
+//! where `it` is computed modulo 2^64 and `t = 0x9e3779b97f4a7c15`.
+//!
+//! # 128-bit variant
+//!
+//! `hash128` extends the digest to 128 bits, using the same final state `(x, y, z, w)`. The lower
+//! word is `H` as above (so it equals the 64-bit digest), and the upper word is
+//!
+//! ```notest
+//! H' = g(g(g(g(x ⊕ l) ⊕ y) ⊕ z) ⊕ w)
+//! ```
+//!
+//! # Portability
+//!
+//! This implementation is the golden oracle of the crate: it reads the blocks byte by byte (so the
+//! host byte order never comes into play), only uses wrapping 64-bit arithmetic, and never depends
+//! on the width of `usize` (beyond the length of the input). As such, it gives the same results on
+//! every target, whether 32-bit or 64-bit, little-endian or big-endian, and every optimized
+//! entry point of the crate is tested against it.
+//!
+//! # Lane order
+//!
+//! In the specification, the blocks go to the lanes round-robin, starting with `a` (the one holding
+//! the seed). Some ports of SeaHash advance the lanes in a different order, which gives a different
+//! (and thus incompatible) hash function. `hash_with_lane_order` implements these orderings (see
+//! `LaneOrder`), so such ports can be checked against this reference.
 
-use diffuse;
+#![deny(clippy::arithmetic_side_effects)]
+
+use {diffuse, diffuse_fast, finalize, finalize_high};
+use consts::{DEFAULT_SEED, INIT_B, INIT_C, INIT_D, BOOST_TWEAK};
 
 /// Read an integer in little-endian.
 fn read_int(int: &[u8]) -> u64 {
@@ -48,26 +97,59 @@ fn read_int(int: &[u8]) -> u64 {
     x
 }
 
+/// The order in which blocks are written to the lanes of the state vector.
+///
+/// Only `LaneOrder::Canonical` is SeaHash. The other orderings give distinct, incompatible hash
+/// functions, which exist solely to check third-party ports against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LaneOrder {
+    /// Round-robin from the first lane: `a, b, c, d, a, b, ...` (the specification).
+    #[default]
+    Canonical,
+    /// Round-robin from the last lane: `d, c, b, a, d, c, ...`.
+    ///
+    /// **Not compatible with SeaHash.**
+    Reversed,
+}
+
+impl LaneOrder {
+    /// Get the lane which the block at some position (modulo 4) is written to.
+    fn lane(self, pos: usize) -> usize {
+        match self {
+            LaneOrder::Canonical => pos,
+            LaneOrder::Reversed => 3 ^ pos,
+        }
+    }
+}
+
 /// A hash state.
 struct State {
     /// The state vector.
     vec: [u64; 4],
-    /// The component of the state vector which is currently being modified.
+    /// The position (modulo 4) of the block which is currently being written.
     cur: usize,
+    /// The order in which the blocks go to the components of the state vector.
+    order: LaneOrder,
+    /// The function used to diffuse the components when written to.
+    diffuse: fn(u64) -> u64,
+    /// The number of blocks written so far.
+    blocks: u64,
+    /// The multiplier of the block index, which is XORed into the blocks (zero for no tweak).
+    tweak: u64,
 }
 
 impl State {
     /// Write a 64-bit integer to the state.
     fn write_u64(&mut self, x: u64) {
-        // Mix it into the substate by XORing it.
-        self.vec[self.cur] ^= x;
+        // Mix it into the substate by XORing it, along with the tweak of its position.
+        let lane = self.order.lane(self.cur);
+        self.blocks = self.blocks.wrapping_add(1);
+        self.vec[lane] ^= x ^ self.blocks.wrapping_mul(self.tweak);
         // Diffuse the component to remove deterministic behavior and commutativity.
-        self.vec[self.cur] = diffuse(self.vec[self.cur]);
+        self.vec[lane] = (self.diffuse)(self.vec[lane]);
 
-        // Increment the cursor.
-        self.cur += 1;
-        // Wrap around.
-        self.cur %= 4;
+        // Increment the cursor and wrap around.
+        self.cur = self.cur.wrapping_add(1) & 3;
     }
 
     /// Calculate the final hash.
@@ -76,14 +158,9 @@ impl State {
         // components are mutually distinct, and thus swapping even and odd chunks will affect the
         // result, because it is sensitive to the initial condition. To add discreteness, we
         // diffuse.
-        diffuse(self.vec[0]
-            ^ self.vec[1]
-            ^ self.vec[2]
-            ^ self.vec[3]
-            // We XOR in the number of written bytes to make it zero-sensitive when excessive bytes
-            // are written (0u32.0u8 ≠ 0u16.0u8).
-            ^ total as u64
-        )
+        // We XOR in the number of written bytes to make it zero-sensitive when excessive bytes
+        // are written (0u32.0u8 ≠ 0u16.0u8).
+        finalize(self.vec, total as u64)
     }
 
     fn with_seed(seed: u64) -> State {
@@ -92,12 +169,17 @@ impl State {
             // the hash function keyed by replacing these.)
             vec: [
                 seed,
-                0xb480a793d8e6c86c,
-                0x6fe2e5aaf078ebc9,
-                0x14f994a4c5259381,
+                INIT_B,
+                INIT_C,
+                INIT_D,
             ],
             // We start at the first component.
             cur: 0,
+            order: LaneOrder::Canonical,
+            diffuse,
+            blocks: 0,
+            // No tweak.
+            tweak: 0,
         }
     }
 }
@@ -109,14 +191,76 @@ impl State {
 /// maximal performance, but this makes code significantly less readable. As such, this version has
 /// only one goal: to make the algorithm readable and understandable.
 pub fn hash(buf: &[u8]) -> u64 {
-    hash_seeded(buf, 0x16f11fe89b0d677c)
+    hash_seeded(buf, DEFAULT_SEED)
 }
 
 /// The seeded version of the reference implementation.
 pub fn hash_seeded(buf: &[u8], seed: u64) -> u64 {
-    // Initialize the state.
+    hash_with_state(buf, State::with_seed(seed))
+}
+
+/// The reference implementation of the fast variant, `hash_fast`.
+pub fn hash_fast(buf: &[u8], seed: u64) -> u64 {
+    let mut state = State::with_seed(seed);
+    // Use a single round of diffusion for the blocks.
+    state.diffuse = diffuse_fast;
+
+    hash_with_state(buf, state)
+}
+
+/// The reference implementation of the boosted variant, `hash_boosted`.
+pub fn hash_boosted(buf: &[u8], seed: u64) -> u64 {
+    let mut state = State::with_seed(seed);
+    // Tweak every block by its position.
+    state.tweak = BOOST_TWEAK;
+
+    hash_with_state(buf, state)
+}
+
+/// The reference implementation of the 128-bit variant, `hash128`.
+pub fn hash128(buf: &[u8], seed: u64) -> u128 {
+    let mut state = State::with_seed(seed);
+    for int in buf.chunks(8) {
+        state.write_u64(read_int(int));
+    }
+
+    let high = finalize_high(state.vec, buf.len() as u64);
+    let low = state.finish(buf.len());
+
+    (high as u128) << 64 | low as u128
+}
+
+/// Hash some buffer with the blocks written to the lanes in some order.
+///
+/// With `LaneOrder::Canonical`, this is `hash_seeded()`. Any other order gives a hash function
+/// which is **not** SeaHash (see `LaneOrder`).
+pub fn hash_with_lane_order(buf: &[u8], seed: u64, order: LaneOrder) -> u64 {
     let mut state = State::with_seed(seed);
+    state.order = order;
 
+    hash_with_state(buf, state)
+}
+
+/// Hash some buffer, reporting every block as it is written.
+///
+/// `step` is called with the block, the lane it went to, and the state of that lane after mixing
+/// it in. Returns the final state vector and the digest.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn hash_traced<F: FnMut(u64, usize, u64)>(buf: &[u8], seed: u64, mut step: F) -> ([u64; 4], u64) {
+    let mut state = State::with_seed(seed);
+    for int in buf.chunks(8) {
+        let lane = state.order.lane(state.cur);
+        let block = read_int(int);
+        state.write_u64(block);
+        step(block, lane, state.vec[lane]);
+    }
+
+    let lanes = state.vec;
+    (lanes, state.finish(buf.len()))
+}
+
+/// Hash some buffer given the initial state.
+fn hash_with_state(buf: &[u8], mut state: State) -> u64 {
     // Partition the rounded down buffer to chunks of 8 bytes, and iterate over them. The last
     // block might not be 8 bytes long.
     for int in buf.chunks(8) {
@@ -127,3 +271,109 @@ pub fn hash_seeded(buf: &[u8], seed: u64) -> u64 {
     // Finish the hash state and return the final value.
     state.finish(buf.len())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lane_order() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..buf.len() {
+            let canonical = hash_with_lane_order(&buf[..n], 42, LaneOrder::Canonical);
+            assert_eq!(canonical, hash_seeded(&buf[..n], 42));
+            assert_eq!(canonical, hash_with_lane_order(&buf[..n], 42, LaneOrder::default()));
+        }
+        // Without blocks, the order makes no difference.
+        assert_eq!(hash_with_lane_order(b"", 42, LaneOrder::Reversed), hash_seeded(b"", 42));
+        for n in 1..buf.len() {
+            assert_ne!(hash_with_lane_order(&buf[..n], 42, LaneOrder::Reversed),
+                       hash_seeded(&buf[..n], 42));
+        }
+    }
+
+    #[test]
+    fn reversed() {
+        // Writing to the lanes in reverse is the same as reversing the initial state vector, i.e.
+        // seeding the last lane.
+        let buf = b"to be or not to be";
+        let mut state = State::with_seed(0);
+        state.vec = [INIT_D, INIT_C, INIT_B, 42];
+
+        assert_eq!(hash_with_lane_order(buf, 42, LaneOrder::Reversed), hash_with_state(buf, state));
+    }
+
+    /// Generate a pseudorandom corpus of buffers with a simple xorshift generator, which is
+    /// portable (unlike e.g. the randomness of the host).
+    fn corpus<F: FnMut(&[u8])>(count: usize, mut f: F) {
+        let mut x = 0x2545f4914f6cdd1du64;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+
+        let mut buf = [0; 4096];
+        for byte in buf.iter_mut() {
+            *byte = next() as u8;
+        }
+        // Every length up to a few blocks, and then random slices of up to 1 KiB (at random
+        // alignments).
+        for len in 0..=130 {
+            f(&buf[..len]);
+        }
+        for _ in 0..count {
+            let len = next() as usize & 1023;
+            let start = next() as usize & 2047;
+            f(&buf[start..start.wrapping_add(len)]);
+        }
+    }
+
+    #[test]
+    fn golden() {
+        // These digests pin the reference implementation down independently of the host, so
+        // running this on any target checks the reference itself.
+        let inputs: [&[u8]; 5] = [b"", b"to be or not to be", b"love is a wonderful terrible thing",
+                                  &[0xFF; 37], &[0; 100]];
+        let digests = [5873265774112565611, 7862665501377589579, 9413369618808500770,
+                       13525661994420244626, 1789613306950382675];
+        for (input, &digest) in inputs.iter().zip(digests.iter()) {
+            assert_eq!(hash_seeded(input, 42), digest);
+        }
+        assert_eq!(hash(b"to be or not to be"), 16114993074217697639);
+    }
+
+    #[test]
+    fn backends() {
+        use core::hash::Hasher;
+
+        use {buffer, hash_ring, hash_multi, hash_raw_parts, SeaHasher};
+
+        let count = if cfg!(miri) { 20 } else { 2000 };
+        corpus(count, |buf| {
+            let seed = (buf.len() as u64).wrapping_mul(BOOST_TWEAK);
+            let expected = hash_seeded(buf, seed);
+
+            assert_eq!(buffer::hash_seeded(buf, seed), expected);
+            assert_eq!(diffuse(buffer::hash_raw(buf, seed)), expected);
+            assert_eq!(unsafe { hash_raw_parts(buf.as_ptr(), buf.len(), seed) }, expected);
+            assert_eq!(buffer::hash_fast(buf, seed), hash_fast(buf, seed));
+            assert_eq!(buffer::hash_boosted(buf, seed), hash_boosted(buf, seed));
+
+            let mut out = [0; 2];
+            hash_multi(buf, &[seed, !seed], &mut out);
+            assert_eq!(out, [expected, hash_seeded(buf, !seed)]);
+
+            let (first, second) = buf.split_at(buf.len().wrapping_div(3));
+            assert_eq!(hash_ring(first, second, seed), expected);
+
+            let mut hasher = SeaHasher::with_seed(seed);
+            for chunk in buf.chunks(13) {
+                hasher.write(chunk);
+            }
+            assert_eq!(hasher.finish(), expected);
+        });
+    }
+}
@@ -0,0 +1,147 @@
+//! Seed derivation.
+
+use {hash, diffuse};
+use consts::{INIT_B, INIT_C, INIT_D};
+
+/// Derive a seed from a human-readable name.
+///
+/// This hashes the string with `hash()` in order to get a well-distributed seed, so
+/// `hash_seeded(buf, seed_from_str("namespace"))` gives a distinct hash function for every
+/// namespace. This allows specifying seeds in e.g. configuration files without magic numbers.
+///
+/// The derivation is deterministic and portable, and it is guaranteed to stay stable: the same
+/// string always yields the same seed.
+pub fn seed_from_str(s: &str) -> u64 {
+    hash(s.as_bytes())
+}
+
+/// Derive `n` independent seeds from one master seed.
+///
+/// This fills `out[..n]` with seeds for `n` distinct hash functions (e.g. for a Bloom filter or
+/// some other sketch), derived deterministically from `master`. The `i`'th seed is the diffusion
+/// of the (diffused) master seed plus `i + 1` times an odd constant, so for a given master seed
+/// the seeds are pairwise distinct (for any `n` up to 2⁶⁴), and well-separated.
+///
+/// # Panics
+///
+/// This panics if `out` is shorter than `n`.
+pub fn derive_seeds(master: u64, n: usize, out: &mut [u64]) {
+    let mut counter = diffuse(master);
+    for seed in &mut out[..n] {
+        counter = counter.wrapping_add(0x9e3779b97f4a7c15);
+        *seed = diffuse(counter);
+    }
+}
+
+/// Check whether a seed makes for an obviously weak hash function.
+///
+/// The seed is the initial value of the first lane, so if it equals the initial value of another
+/// lane, the two lanes start out identical, and swapping the blocks going to them (e.g. the first
+/// two words of a 16-byte input) leaves the digest unchanged. These seeds should not be used. Any
+/// other seed is fine: the diffusion function is bijective, so no seed can make it degenerate.
+pub fn is_trivial_seed(seed: u64) -> bool {
+    seed == INIT_B || seed == INIT_C || seed == INIT_D
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable() {
+        assert_eq!(seed_from_str("cache-v2"), seed_from_str("cache-v2"));
+        assert_eq!(seed_from_str("cache-v2"), 16590497334004325095);
+    }
+
+    #[test]
+    fn distinct() {
+        assert_ne!(seed_from_str("cache-v1"), seed_from_str("cache-v2"));
+        assert_ne!(seed_from_str(""), seed_from_str("a"));
+        assert_ne!(seed_from_str("ab"), seed_from_str("ba"));
+    }
+
+    #[test]
+    fn derived_distinct() {
+        let mut seeds = [0; 1000];
+        derive_seeds(42, 1000, &mut seeds);
+
+        for (i, a) in seeds.iter().enumerate() {
+            assert!(seeds[i + 1..].iter().all(|b| a != b));
+        }
+
+        let mut again = [0; 1000];
+        derive_seeds(42, 1000, &mut again);
+        assert_eq!(&seeds[..], &again[..]);
+
+        // The seeds of fewer functions are a prefix, and the rest is left alone.
+        let mut prefix = [7; 10];
+        derive_seeds(42, 4, &mut prefix);
+        assert_eq!(&prefix[..4], &seeds[..4]);
+        assert_eq!(&prefix[4..], &[7; 6]);
+
+        let mut other = [0; 4];
+        derive_seeds(43, 4, &mut other);
+        assert_ne!(other, prefix[..4]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn bloom() {
+        use hash_seeded;
+
+        const BITS: usize = 1 << 12;
+
+        let mut seeds = [0; 4];
+        derive_seeds(seed_from_str("bloom"), 4, &mut seeds);
+
+        let mut filter = [false; BITS];
+        for key in 0..200u32 {
+            for &seed in &seeds {
+                filter[hash_seeded(&key.to_le_bytes(), seed) as usize % BITS] = true;
+            }
+        }
+
+        let contains = |key: u32| {
+            seeds.iter().all(|&seed| filter[hash_seeded(&key.to_le_bytes(), seed) as usize % BITS])
+        };
+        assert!((0..200).all(contains));
+        // About 800 of the 4096 bits are set, so the false positive rate is about 0.1%.
+        assert!((200..10200).filter(|&key| contains(key)).count() < 50);
+
+        // The functions are uncorrelated on the same key: the digests differ in half the bits.
+        for (i, &a) in seeds.iter().enumerate() {
+            for &b in &seeds[i + 1..] {
+                let diff: u32 = (0..1000u32).map(|key| {
+                    let key = key.to_le_bytes();
+                    (hash_seeded(&key, a) ^ hash_seeded(&key, b)).count_ones()
+                }).sum();
+
+                assert!((diff as f64 / 1000.0 - 32.0).abs() < 0.5, "{}", diff);
+            }
+        }
+    }
+
+    #[test]
+    fn trivial() {
+        use hash_seeded;
+        use consts::DEFAULT_SEED;
+
+        assert!(!is_trivial_seed(DEFAULT_SEED));
+        assert!(!is_trivial_seed(0));
+        assert!(!is_trivial_seed(seed_from_str("cache-v2")));
+
+        let mut buf = [0; 16];
+        buf[..8].copy_from_slice(&1u64.to_le_bytes());
+        buf[8..].copy_from_slice(&2u64.to_le_bytes());
+        let mut swapped = [0; 16];
+        swapped[..8].copy_from_slice(&2u64.to_le_bytes());
+        swapped[8..].copy_from_slice(&1u64.to_le_bytes());
+
+        assert_ne!(hash_seeded(&buf, DEFAULT_SEED), hash_seeded(&swapped, DEFAULT_SEED));
+        // With a trivial seed, swapping the blocks of the first two lanes collides.
+        assert!(is_trivial_seed(INIT_B));
+        assert_eq!(hash_seeded(&buf, INIT_B), hash_seeded(&swapped, INIT_B));
+        assert!(is_trivial_seed(INIT_C));
+        assert!(is_trivial_seed(INIT_D));
+    }
+}
@@ -0,0 +1,70 @@
+//! Hashing of the buffers of the `bytes` crate.
+
+use core::hash::Hasher;
+
+use bytes::{Buf, Bytes};
+
+use {hash_seeded, SeaHasher};
+
+/// Hash a `Bytes` according to a chosen seed.
+///
+/// This hashes the underlying slice directly, without copying, i.e. it is the same as
+/// `hash_seeded(&b[..], seed)`. `BytesMut` can be hashed the same way through `hash_seeded()` (or
+/// `hash_ref()`), as it dereferences to a slice as well.
+pub fn hash_bytes_crate(b: &Bytes, seed: u64) -> u64 {
+    hash_seeded(b, seed)
+}
+
+/// Hash the remaining bytes of some (possibly non-contiguous) `Buf` according to a chosen seed.
+///
+/// This consumes the buffer chunk by chunk (e.g. every part of a `Chain`), streaming the chunks
+/// into a `SeaHasher`, so the result is the same as `hash_seeded()` of their concatenation,
+/// without copying them.
+pub fn hash_buf<B: Buf>(mut buf: B, seed: u64) -> u64 {
+    let mut hasher = SeaHasher::with_seed(seed);
+    while buf.has_remaining() {
+        let chunk = buf.chunk();
+        hasher.write(chunk);
+
+        let len = chunk.len();
+        buf.advance(len);
+    }
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bytes::BytesMut;
+
+    #[test]
+    fn contiguous() {
+        let b = Bytes::from_static(b"to be or not to be");
+        assert_eq!(hash_bytes_crate(&b, 42), hash_seeded(b"to be or not to be", 42));
+        assert_eq!(hash_buf(b.clone(), 42), hash_seeded(b"to be or not to be", 42));
+
+        let mut m = BytesMut::new();
+        m.extend_from_slice(b"to be or not to be");
+        assert_eq!(hash_seeded(&m, 42), hash_bytes_crate(&m.freeze(), 42));
+    }
+
+    #[test]
+    fn chained() {
+        let data: [u8; 100] = core::array::from_fn(|i| (i * 3 + 1) as u8);
+        let parts = [
+            Bytes::copy_from_slice(&data[..3]),
+            Bytes::new(),
+            Bytes::copy_from_slice(&data[3..40]),
+            Bytes::copy_from_slice(&data[40..]),
+        ];
+
+        let chain = parts[0].clone()
+            .chain(parts[1].clone())
+            .chain(parts[2].clone())
+            .chain(parts[3].clone());
+        assert_eq!(hash_buf(chain, 42), hash_seeded(&data, 42));
+        assert_eq!(hash_buf(Bytes::new(), 42), hash_seeded(&[], 42));
+    }
+}
@@ -0,0 +1,92 @@
+//! Known-answer tests of the implementations, for use at runtime.
+
+use core::hash::Hasher;
+use core::hint::black_box;
+
+use {hash, hash_seeded, reference, SeaHasher};
+
+/// The seed of the test vectors.
+const SEED: u64 = 0x5ea;
+
+/// The test vectors: the length of a prefix of the test buffer, and its digest under `SEED`.
+///
+/// The lengths cover the empty input, the partial word, the medium path, the straight-line 16 and
+/// 32 byte paths, and the main loop followed by a tail.
+const VECTORS: [(usize, u64); 8] = [
+    (0, 6529577406038626866),
+    (3, 9535189024855701295),
+    (8, 17771473661837687597),
+    (13, 12140029424512042067),
+    (16, 14173521558957928026),
+    (32, 6428092582413653683),
+    (57, 8474258869503206915),
+    (100, 13084335157854570279),
+];
+
+/// Check the implementations against known test vectors.
+///
+/// This hashes fixed inputs with the one-shot function, the streaming hasher and the reference
+/// implementation, and compares the digests against the ones recorded from a correct build.
+/// Returns `true` if all of them match. A mismatch means that the build is broken (e.g.
+/// miscompiled, or built for a target whose assumptions don't hold), so the digests can't be
+/// trusted.
+///
+/// The inputs go through `black_box`, so the checks are done at runtime rather than folded by the
+/// compiler. With the `self-test-on-load` feature, this runs automatically before `main`.
+pub fn self_test() -> bool {
+    let mut buf = [0; 100];
+    for (i, x) in buf.iter_mut().enumerate() {
+        *x = (i * 7 + 1) as u8;
+    }
+    let buf = black_box(buf);
+
+    let vectors_ok = VECTORS.iter().all(|&(len, expected)| {
+        let data = &buf[..len];
+
+        // Feed the hasher in uneven writes, to go through the excessive bytes.
+        let mut hasher = SeaHasher::with_seed(SEED);
+        for chunk in data.chunks(5) {
+            hasher.write(chunk);
+        }
+
+        hash_seeded(data, SEED) == expected
+            && hasher.finish() == expected
+            && reference::hash_seeded(data, SEED) == expected
+    });
+
+    vectors_ok && hash(black_box(b"to be or not to be")) == 16114993074217697639
+}
+
+/// Run `self_test()` before `main`, aborting if it fails.
+///
+/// This aborts rather than panics, as unwinding out of a load-time constructor is not allowed.
+#[cfg(feature = "self-test-on-load")]
+#[::ctor::ctor]
+fn self_test_on_load() {
+    if !self_test() {
+        eprintln!("seahash: the self-test failed, so this build computes wrong digests.");
+        ::std::process::abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes() {
+        assert!(self_test());
+    }
+
+    #[test]
+    fn vectors() {
+        let mut buf = [0; 100];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7 + 1) as u8;
+        }
+
+        for &(len, expected) in &VECTORS {
+            assert_eq!(hash_seeded(&buf[..len], SEED), expected);
+        }
+    }
+}
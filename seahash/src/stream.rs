@@ -1,66 +1,965 @@
+#![deny(clippy::arithmetic_side_effects)]
+
 use core::hash::Hasher;
+use core::hint::black_box;
 
-use {hash_seeded, diffuse};
+use {buffer, diffuse, finalize, undiffuse};
+use consts::{DEFAULT_SEED, INIT_B, INIT_C, INIT_D};
 
 /// The streaming version of the algorithm.
 ///
-/// Note that the input type is not taken into account, and thus is assumed to be fixed.
+/// The written bytes are treated as one continuous stream, meaning that the digest does not depend
+/// on how the input is split into writes: `finish()` gives the same value as `hash_seeded()` over
+/// the concatenation of everything written. Integers are written in little-endian, so the result
+/// is portable as well.
+///
+/// Up to version 2.1.1, the hasher instead chained the digests of the individual writes, so its
+/// digests differ from the ones of this hasher for any input (see `CHANGELOG.md`).
+///
+/// The hasher never allocates: the bytes which do not yet make up a full word are kept inline, so
+/// the whole state lives on the stack (as witnessed by it being `Copy`). As such, it works in
+/// bare-metal `no_std` environments without `alloc`.
+///
+/// `finish()` does not consume or modify the hasher (as per the contract of `Hasher`): it computes
+/// the digest of the bytes written so far. As such, it can be called any number of times, and
+/// writing after it is allowed and simply continues the stream, i.e. the next `finish()` gives
+/// the digest of everything written since the hasher was created. No state is tracked for this,
+/// so there is no overhead. To start over, create a new hasher.
+///
+/// With the `nightly` feature, the unstable `Hasher::write_length_prefix()` and
+/// `Hasher::write_str()` are overridden, so strings are framed by their length as a 64-bit integer
+/// (like collections), rather than by a trailing `0xFF` byte. This changes the digests of strings
+/// hashed through `Hash`, but not the ones of bytes written directly.
+#[derive(Clone, Copy)]
 pub struct SeaHasher {
-    /// The state of the hasher.
-    state: u64,
-    /// The seed of the hasher.
-    seed: u64,
+    /// The state vector.
+    state: [u64; 4],
+    /// The component of the state vector which the next full word is written to.
+    cursor: usize,
+    /// The excessive bytes (which do not yet form a full word) in little-endian.
+    tail: u64,
+    /// The number of excessive bytes in `tail`.
+    ntail: usize,
+    /// The total number of bytes written.
+    written: u64,
+    /// The salt, which is mixed into the final fold.
+    salt: u64,
+}
+
+/// The state of a `SeaHasher` with a fixed, C-compatible layout.
+///
+/// This allows passing the state of a hasher across an FFI boundary, or serializing and inspecting
+/// it from C. The layout (on any platform) is:
+///
+/// | Offset | Size | Field      |
+/// |--------|------|------------|
+/// | 0      | 32   | `state`    |
+/// | 32     | 4    | `cursor`   |
+/// | 36     | 8    | `buffered` |
+/// | 44     | 4    | `buf_len`  |
+/// | 48     | 8    | `total`    |
+/// | 56     | 8    | `salt`     |
+///
+/// with a total size of 64 bytes and an alignment of 8 bytes. The integers are in native byte
+/// order.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeaHasherRaw {
+    /// The state vector.
+    pub state: [u64; 4],
+    /// The component of the state vector which the next full word is written to (less than 4).
+    pub cursor: u32,
+    /// The bytes written, but not yet forming a full word (in the order they were written).
+    pub buffered: [u8; 8],
+    /// The number of bytes in `buffered` (less than 8).
+    pub buf_len: u32,
+    /// The total number of bytes written.
+    pub total: u64,
+    /// The salt (zero unless constructed with `SeaHasher::with_seed_and_salt()`).
+    pub salt: u64,
+}
+
+/// An immutable snapshot of the state of a `SeaHasher`.
+///
+/// This is created by `SeaHasher::snapshot()` and turned back into a hasher by
+/// `SeaHasher::from_snapshot()`. It is a small `Copy` value (the state vector, the cursor and the
+/// buffered bytes), so a long common prefix can be hashed once and its snapshot stamped onto many
+/// derived hashes.
+#[derive(Clone, Copy)]
+pub struct HashSnapshot {
+    /// The state of the hasher at the time of the snapshot.
+    hasher: SeaHasher,
 }
 
 impl Default for SeaHasher {
     fn default() -> SeaHasher {
-        SeaHasher::with_seed(0xe7b0c93ca8525013)
+        SeaHasher::with_seed(DEFAULT_SEED)
     }
 }
 
 impl SeaHasher {
     /// Create a new `SeaHasher` with default state.
+    ///
+    /// The digest of this hasher matches `hash()`.
     pub fn new() -> SeaHasher {
         SeaHasher::default()
     }
 
     /// Construct a new `SeaHasher` given some seed.
+    ///
+    /// The digest of this hasher matches `hash_seeded()` with the same seed.
     pub fn with_seed(seed: u64) -> SeaHasher {
         SeaHasher {
-            state: 0xba663d61fe3aa408,
-            seed: seed,
+            state: [seed, INIT_B, INIT_C, INIT_D],
+            cursor: 0,
+            tail: 0,
+            ntail: 0,
+            written: 0,
+            salt: 0,
+        }
+    }
+
+    /// Construct a new `SeaHasher` with an arbitrary initial state vector.
+    pub(crate) fn with_state(state: [u64; 4]) -> SeaHasher {
+        SeaHasher {
+            state,
+            ..SeaHasher::with_seed(0)
+        }
+    }
+
+    /// Construct a new `SeaHasher` given some seed and a salt.
+    ///
+    /// Contrary to the seed, which defines the initial state of the first lane, the salt is XOR'd
+    /// into the folded state right before the final diffusion. This is cheap, but it only relabels
+    /// the digests: as the diffusion is bijective, the salted digest is a fixed bijection of the
+    /// unsalted one, so two inputs collide under some salt if and only if they collide without it.
+    /// As such, the salt gives **no** independence of collisions. What it does change is which
+    /// inputs share a truncated digest (e.g. a bucket of a hash table). A salt of zero gives the
+    /// same digest as `with_seed()`.
+    pub fn with_seed_and_salt(seed: u64, salt: u64) -> SeaHasher {
+        SeaHasher {
+            salt,
+            ..SeaHasher::with_seed(seed)
+        }
+    }
+
+    /// Get the lane (component of the state vector) which the next bytes are mixed into.
+    ///
+    /// Every 8 bytes written make up a word, and the words go to the lanes 0, 1, 2, 3, 0, 1, ... in
+    /// turn. If a word is partially written, this is the lane it will go to.
+    pub fn current_lane(&self) -> usize {
+        self.cursor
+    }
+
+    /// Check whether the stream is at a boundary of 32-byte blocks.
+    ///
+    /// This is the case if no partial word is buffered and the next word goes to the first lane,
+    /// i.e. if a multiple of 32 bytes was written (including none). At such a boundary, the state
+    /// of the hasher consists of the lanes and the length alone (see `to_raw()`), which is the
+    /// precondition for e.g. parallel hashing schemes to combine it with the state of another.
+    pub fn is_block_aligned(&self) -> bool {
+        self.ntail == 0 && self.cursor == 0
+    }
+
+    /// Write a buffer in chunks of `stride` bytes (the last chunk may be shorter).
+    ///
+    /// This is equivalent to `write(buf)`, as the result doesn't depend on how the input is split
+    /// into writes. It exists to reproduce particular write patterns (e.g. DMA strides) in
+    /// benchmarks and tests of the chunk boundary handling.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `stride` is zero.
+    pub fn write_in_strides(&mut self, buf: &[u8], stride: usize) {
+        assert!(stride > 0, "The stride must be nonzero.");
+
+        for chunk in buf.chunks(stride) {
+            self.write(chunk);
+        }
+    }
+
+    /// Pad the stream with zero bytes until the next write starts a word in some lane.
+    ///
+    /// This completes the partially written word (if any) with zeros, and then writes zero words
+    /// until `lane` is the current lane. Nothing is written if the stream is already there. The
+    /// padding is part of the stream (so it counts towards the length), meaning that the result
+    /// is equivalent to writing the same zeros explicitly, and thus fully reproducible.
+    ///
+    /// This allows placing fields of a structured record at predictable lanes. Note that this
+    /// only makes sense with the same layout on the writing and the verifying end.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `lane` is not less than 4.
+    pub fn align_to_lane(&mut self, lane: usize) {
+        assert!(lane < 4, "There are only 4 lanes.");
+
+        if self.ntail > 0 {
+            // Complete the partial word.
+            let ntail = self.ntail;
+            self.write_int(0, 8usize.wrapping_sub(ntail));
+        }
+        while self.cursor != lane {
+            self.write_int(0, 8);
+        }
+    }
+
+    /// Revert the last word written with `write_u64()`.
+    ///
+    /// This restores the lane which `x` was mixed into to its previous state, and moves the
+    /// cursor back to that lane, as if `x` had never been written. The caller must supply the exact
+    /// word which was written last: no record of it is kept, so unwriting any other word silently
+    /// leaves the hasher in a state which no sequence of writes leads to. This allows e.g. a
+    /// sliding window over an append-only log to drop its most recent word.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the stream is not word-aligned (the bytes completing the previous partial
+    /// word would be lost), or if fewer than 8 bytes were written.
+    pub fn unwrite_u64(&mut self, x: u64) {
+        assert!(self.ntail == 0, "The stream must be word-aligned.");
+        assert!(self.written >= 8, "There is no word to unwrite.");
+
+        self.written = self.written.wrapping_sub(8);
+        // Go to the previous component and wrap around.
+        self.cursor = self.cursor.wrapping_add(3) & 3;
+        self.state[self.cursor] = undiffuse(self.state[self.cursor]) ^ x;
+    }
+
+    /// Get the state of the hasher in the C-compatible layout.
+    pub fn to_raw(&self) -> SeaHasherRaw {
+        SeaHasherRaw {
+            state: self.state,
+            cursor: self.cursor as u32,
+            buffered: self.tail.to_le_bytes(),
+            buf_len: self.ntail as u32,
+            total: self.written,
+            salt: self.salt,
+        }
+    }
+
+    /// Restore a hasher from its state in the C-compatible layout.
+    ///
+    /// This returns `None` if the state is invalid, i.e. if the cursor or the number of buffered
+    /// bytes is out of range. Buffered bytes past `buf_len` are ignored.
+    pub fn from_raw(raw: SeaHasherRaw) -> Option<SeaHasher> {
+        if raw.cursor >= 4 || raw.buf_len >= 8 {
+            return None;
+        }
+
+        // Mask out the bytes which are not buffered.
+        let mask = (1u64 << raw.buf_len.wrapping_mul(8)).wrapping_sub(1);
+
+        Some(SeaHasher {
+            state: raw.state,
+            cursor: raw.cursor as usize,
+            tail: u64::from_le_bytes(raw.buffered) & mask,
+            ntail: raw.buf_len as usize,
+            written: raw.total,
+            salt: raw.salt,
+        })
+    }
+
+    /// Resume hashing from a checkpoint taken by `into_parts()`.
+    ///
+    /// `lanes` is the state vector, `cursor` is the lane which the next word goes to, and `total`
+    /// is the number of bytes written so far, which must be a multiple of 8. Continuing from the
+    /// restored hasher gives the same digest as continuing the hasher which the parts were taken
+    /// from.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `cursor` is not less than 4 or if `total` is not a multiple of 8.
+    pub fn from_parts(lanes: [u64; 4], cursor: usize, total: u64) -> SeaHasher {
+        assert!(cursor < 4, "There are only 4 lanes.");
+        assert!(total & 7 == 0, "The checkpoint must be on a word boundary.");
+
+        SeaHasher {
+            cursor,
+            written: total,
+            ..SeaHasher::with_state(lanes)
+        }
+    }
+
+    /// Take a minimal checkpoint of the hasher, to be resumed by `from_parts()`.
+    ///
+    /// This returns the state vector, the current lane and the number of bytes written. That is
+    /// only the whole state if no partial word is buffered (i.e. if a multiple of 8 bytes was
+    /// written) and there is no salt, so `None` is returned otherwise. For the complete state,
+    /// use `to_raw()`.
+    pub fn into_parts(self) -> Option<([u64; 4], usize, u64)> {
+        if self.ntail != 0 || self.salt != 0 {
+            return None;
+        }
+
+        Some((self.state, self.cursor, self.written))
+    }
+
+    /// Get the digest together with the lanes it is folded from.
+    ///
+    /// The lanes are the components of the state vector after the excessive bytes are written
+    /// (padded with zeros), so the digest is `diffuse(a ^ b ^ c ^ d ^ len)`, where `len` is the
+    /// number of bytes written (XORed with the salt, if any). This is meant for debugging, e.g. to
+    /// check whether some lane is degenerate for a particular key distribution.
+    pub fn finish_debug(&self) -> (u64, [u64; 4]) {
+        (self.finish(), self.final_lanes())
+    }
+
+    /// Get the state vector with the excessive bytes written, i.e. right before folding.
+    fn final_lanes(&self) -> [u64; 4] {
+        let mut state = self.state;
+
+        // Write the excessive bytes as if they were padded with zeros.
+        if self.ntail > 0 {
+            state[self.cursor] = diffuse(state[self.cursor] ^ self.tail);
+        }
+
+        state
+    }
+
+    /// Take a snapshot of the state of the hasher.
+    pub fn snapshot(&self) -> HashSnapshot {
+        HashSnapshot { hasher: *self }
+    }
+
+    /// Restore a hasher from a snapshot.
+    ///
+    /// Writing to the restored hasher is equivalent to writing to the hasher which the snapshot
+    /// was taken of.
+    pub fn from_snapshot(snapshot: HashSnapshot) -> SeaHasher {
+        snapshot.hasher
+    }
+
+    /// Write an integer of some number of bytes (at most 8) in little-endian.
+    ///
+    /// This is equivalent to writing the lowest `bytes` bytes of `x`, but it splices the integer
+    /// into the excessive bytes directly, rather than going through the bytes one by one. `x` must
+    /// not have any bits set above the lowest `bytes` bytes.
+    #[inline]
+    fn write_int(&mut self, x: u64, bytes: usize) {
+        self.written = self.written.wrapping_add(bytes as u64);
+
+        if self.ntail == 0 && bytes == 8 {
+            // We are word-aligned, so the integer makes up a full word.
+            self.push_word(x);
+            return;
+        }
+
+        // Append the integer to the excessive bytes (the shift is less than 64, as `ntail < 8`).
+        self.tail |= x << self.ntail.wrapping_mul(8);
+        let filled = self.ntail.wrapping_add(bytes);
+        if filled < 8 {
+            // Not enough to fill a word yet.
+            self.ntail = filled;
+            return;
         }
+
+        // The excessive bytes make up a full word now.
+        let tail = self.tail;
+        self.push_word(tail);
+
+        // Keep the bytes of the integer which did not fit.
+        self.ntail = filled.wrapping_sub(8);
+        self.tail = if self.ntail == 0 {
+            0
+        } else {
+            x >> bytes.wrapping_sub(self.ntail).wrapping_mul(8)
+        };
     }
+
+    /// Write a full 64-bit word into the current component of the state vector.
+    #[inline(always)]
+    fn push_word(&mut self, x: u64) {
+        self.state[self.cursor] = diffuse(self.state[self.cursor] ^ x);
+        // Go to the next component and wrap around.
+        self.cursor = self.cursor.wrapping_add(1) & 3;
+    }
+}
+
+/// Read the first 8 bytes of some buffer as a little-endian word.
+#[inline(always)]
+fn read_word(bytes: &[u8]) -> u64 {
+    let mut word = [0; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
 }
 
 impl Hasher for SeaHasher {
     fn finish(&self) -> u64 {
-        diffuse(self.state)
+        // Fold the state vector and XOR the length, like the one-shot version does.
+        finalize(self.final_lanes(), self.written ^ self.salt)
     }
 
     fn write(&mut self, bytes: &[u8]) {
-        self.state ^= hash_seeded(bytes, self.seed);
-        self.state = diffuse(self.state);
-    }
+        self.written = self.written.wrapping_add(bytes.len() as u64);
 
-    fn write_u64(&mut self, n: u64) {
-        self.state ^= n;
-        self.state = diffuse(self.state);
+        let mut bytes = bytes;
+        // Top up the excessive bytes from the previous write first.
+        if self.ntail > 0 {
+            while self.ntail < 8 && !bytes.is_empty() {
+                self.tail |= (bytes[0] as u64) << self.ntail.wrapping_mul(8);
+                self.ntail = self.ntail.wrapping_add(1);
+                bytes = &bytes[1..];
+            }
+
+            if self.ntail < 8 {
+                // Not enough to fill a word yet.
+                return;
+            }
+
+            let tail = self.tail;
+            self.push_word(tail);
+            self.tail = 0;
+            self.ntail = 0;
+        }
+
+        // Write single words until the cursor is back at the first component, so that the
+        // following blocks line up with the state vector.
+        while self.cursor != 0 && bytes.len() >= 8 {
+            let x = read_word(bytes);
+            self.push_word(x);
+            bytes = &bytes[8..];
+        }
+
+        // Write the whole blocks through the kernel of the one-shot version.
+        if self.cursor == 0 {
+            let ([a, b, c, d], rest) = buffer::write_blocks(self.state, bytes);
+            // Storing the lanes next to each other lets LLVM vectorize the loop over them, which
+            // is considerably slower on x86-64, as SSE2 has no 64-bit multiplication. Hiding them
+            // from the optimizer one by one keeps them in general-purpose registers.
+            self.state = [black_box(a), black_box(b), black_box(c), black_box(d)];
+            bytes = rest;
+        }
+
+        // Write the remaining full words.
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.push_word(read_word(word));
+        }
+
+        // Keep the remaining bytes around for the next write.
+        for &byte in words.remainder() {
+            self.tail |= (byte as u64) << self.ntail.wrapping_mul(8);
+            self.ntail = self.ntail.wrapping_add(1);
+        }
     }
 
+    #[inline]
     fn write_u8(&mut self, n: u8) {
-        self.write_u64(n as u64);
+        // This is the hot path of byte-at-a-time hashing, so rather than going through the general
+        // `write_int()`, shift the byte into the excessive bytes, and only diffuse once they make
+        // up a word.
+        self.written = self.written.wrapping_add(1);
+        self.tail |= (n as u64) << self.ntail.wrapping_mul(8);
+        self.ntail = self.ntail.wrapping_add(1);
+
+        if self.ntail == 8 {
+            let tail = self.tail;
+            self.push_word(tail);
+            self.tail = 0;
+            self.ntail = 0;
+        }
     }
 
     fn write_u16(&mut self, n: u16) {
-        self.write_u64(n as u64);
+        self.write_int(n as u64, 2);
     }
 
     fn write_u32(&mut self, n: u32) {
-        self.write_u64(n as u64);
+        self.write_int(n as u64, 4);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write_int(n, 8);
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        self.write_int(n as u64, 8);
+        self.write_int((n >> 64) as u64, 8);
     }
 
     fn write_usize(&mut self, n: usize) {
+        // Always write it as 64-bit to avoid depending on the pointer width.
         self.write_u64(n as u64);
     }
+
+    #[cfg(feature = "nightly")]
+    fn write_length_prefix(&mut self, len: usize) {
+        // The length makes up a full 64-bit integer, like with `write_usize()`.
+        self.write_u64(len as u64);
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_str(&mut self, s: &str) {
+        // Frame the string by its length, rather than by the `0xFF` byte appended by the default
+        // implementation, which writes a single byte and thus misaligns the following words.
+        self.write_length_prefix(s.len());
+        self.write(s.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use {hash, hash_seeded};
+
+    #[test]
+    fn exact_words() {
+        // The excessive bytes are empty here, so `finish()` must not write an extra word.
+        let buf = b"love is a wonderful terrible thing, love is a wonder";
+        for &n in &[8, 16, 24, 32, 40] {
+            for split in 0..n {
+                let mut h = SeaHasher::new();
+                h.write(&buf[..split]);
+                h.write(&buf[split..n]);
+                assert_eq!(h.finish(), hash(&buf[..n]));
+                assert_eq!(h.finish(), ::reference::hash(&buf[..n]));
+            }
+
+            let mut h = SeaHasher::new();
+            for &byte in &buf[..n] {
+                h.write_u8(byte);
+            }
+            assert_eq!(h.finish(), hash(&buf[..n]));
+        }
+    }
+
+    #[test]
+    fn no_panic() {
+        // This runs with overflow checks in the test profile, so any non-wrapping arithmetic on
+        // the length counter or the cursor would panic here.
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 31) as u8;
+        }
+        for len in 0..buf.len() {
+            let mut hasher = SeaHasher::with_seed(len as u64);
+            for chunk in buf[..len].chunks(len % 11 + 1) {
+                hasher.write(chunk);
+                hasher.write_u8(chunk[0]);
+                hasher.write_u32(len as u32);
+            }
+            let _ = hasher.finish();
+            assert_eq!(hash(&buf[..len]), ::reference::hash(&buf[..len]));
+        }
+
+        // Restore a hasher which is about to wrap its length counter.
+        let mut raw = SeaHasher::new().to_raw();
+        raw.total = u64::MAX;
+        let mut hasher = SeaHasher::from_raw(raw).unwrap();
+        hasher.write(b"to be or not to be");
+        hasher.write_u64(42);
+        hasher.write_u8(1);
+
+        // The counter wraps around, so the digest is that of a hasher which counted from zero.
+        let mut expected = SeaHasher::new();
+        expected.write(b"to be or not to be");
+        expected.write_u64(42);
+        expected.write_u8(1);
+        let mut raw = expected.to_raw();
+        raw.total -= 1;
+        assert_eq!(hasher.finish(), SeaHasher::from_raw(raw).unwrap().finish());
+    }
+
+    #[test]
+    fn chunked() {
+        let mut buf = [0; 1024];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 7) as u8;
+        }
+
+        for chunk in 1..70 {
+            let mut h = SeaHasher::with_seed(500);
+            for part in buf.chunks(chunk) {
+                h.write(part);
+            }
+            assert_eq!(h.finish(), hash_seeded(&buf, 500));
+        }
+    }
+
+    #[test]
+    fn blocks() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 13 + 5) as u8;
+        }
+
+        // The prefix leaves the cursor and the excessive bytes anywhere, so the long write has to
+        // catch up with the state vector before it goes through the blocks.
+        for split in 0..40 {
+            for &n in &[split, split + 32, split + 63, split + 64, 300] {
+                let mut h = SeaHasher::with_seed(7);
+                h.write(&buf[..split]);
+                h.write(&buf[split..n]);
+                assert_eq!(h.finish(), hash_seeded(&buf[..n], 7));
+            }
+        }
+    }
+
+    #[test]
+    fn strides() {
+        let mut buf = [0; 300];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = (i * 11 + 3) as u8;
+        }
+
+        for stride in 1..=40 {
+            for &n in &[0, 1, 7, 8, 31, 32, 33, 100, 300] {
+                let mut h = SeaHasher::with_seed(42);
+                h.write_in_strides(&buf[..n], stride);
+                assert_eq!(h.finish(), hash_seeded(&buf[..n], 42));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_stride() {
+        SeaHasher::new().write_in_strides(b"abc", 0);
+    }
+
+    #[test]
+    fn prefixes() {
+        let buf = b"love is a wonderful terrible thing";
+        for n in 0..buf.len() {
+            let mut h = SeaHasher::new();
+            h.write(&buf[..n]);
+            assert_eq!(h.finish(), hash(&buf[..n]));
+        }
+    }
+
+    #[test]
+    fn integers() {
+        let mut a = SeaHasher::new();
+        a.write_u8(1);
+        a.write_u16(2);
+        a.write_u32(3);
+        a.write_u64(4);
+        a.write_usize(5);
+
+        let mut b = SeaHasher::new();
+        b.write(&[1, 2, 0, 3, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(a.finish(), b.finish());
+    }
+
+    #[test]
+    fn bytes() {
+        let buf = b"love is a wonderful terrible thing";
+
+        for n in 0..buf.len() {
+            let mut h = SeaHasher::with_seed(42);
+            for &byte in &buf[..n] {
+                h.write_u8(byte);
+            }
+            assert_eq!(h.finish(), hash_seeded(&buf[..n], 42));
+
+            // Mixed with bulk writes.
+            h.write(&buf[n..]);
+            assert_eq!(h.finish(), hash_seeded(&buf[..], 42));
+        }
+    }
+
+    #[test]
+    fn integers_unaligned() {
+        // Write integers at every offset within a word, and compare them against their bytes.
+        for offset in 0..8 {
+            let prefix = [0xAB; 8];
+
+            let mut a = SeaHasher::new();
+            a.write(&prefix[..offset]);
+            a.write_u8(0x01);
+            a.write_u64(0x0203040506070809);
+            a.write_u16(0x0A0B);
+            a.write_u128(0x0C0D0E0F101112131415161718191A1B);
+            a.write_u32(0x1C1D1E1F);
+            a.write_u64(!0);
+
+            let mut b = SeaHasher::new();
+            b.write(&prefix[..offset]);
+            b.write(&[0x01]);
+            b.write(&0x0203040506070809u64.to_le_bytes());
+            b.write(&0x0A0Bu16.to_le_bytes());
+            b.write(&0x0C0D0E0F101112131415161718191A1Bu128.to_le_bytes());
+            b.write(&0x1C1D1E1Fu32.to_le_bytes());
+            b.write(&[0xFF; 8]);
+
+            assert_eq!(a.finish(), b.finish());
+            assert_eq!(a.to_raw(), b.to_raw());
+        }
+    }
+
+    #[test]
+    fn stack_resident() {
+        // Only types without heap ownership can be `Copy`.
+        fn assert_copy<T: Copy>() {}
+        assert_copy::<SeaHasher>();
+        assert!(core::mem::size_of::<SeaHasher>() <= 80);
+
+        // Stream some bytes, using nothing but `core`. This still links `std`; the build without
+        // it is checked by the `no-std-check` crate.
+        let buf = [0x42; 100];
+        let mut h = SeaHasher::with_seed(7);
+        for byte in buf.chunks(3) {
+            h.write(byte);
+        }
+        let copy = h;
+
+        assert_eq!(h.finish(), hash_seeded(&buf, 7));
+        assert_eq!(copy.finish(), h.finish());
+    }
+
+    #[test]
+    fn nested() {
+        use core::hash::Hash;
+
+        fn digest(x: &[&[&str]]) -> u64 {
+            let mut h = SeaHasher::new();
+            x.hash(&mut h);
+            h.finish()
+        }
+
+        let structures: [&[&[&str]]; 7] = [
+            &[&["ab"], &["c"]],
+            &[&["a"], &["bc"]],
+            &[&["abc"]],
+            &[&["a", "bc"]],
+            &[&["ab", "c"]],
+            &[&["abc"], &[]],
+            &[&[], &["abc"]],
+        ];
+        for (i, a) in structures.iter().enumerate() {
+            for b in &structures[i + 1..] {
+                assert_ne!(digest(a), digest(b));
+            }
+            assert_eq!(digest(a), digest(a));
+        }
+    }
+
+    #[test]
+    fn write_after_finish() {
+        let mut h = SeaHasher::with_seed(42);
+        h.write(b"to be or ");
+        assert_eq!(h.finish(), hash_seeded(b"to be or ", 42));
+        assert_eq!(h.finish(), hash_seeded(b"to be or ", 42));
+
+        h.write(b"not to be");
+        assert_eq!(h.finish(), hash_seeded(b"to be or not to be", 42));
+    }
+
+    #[test]
+    fn lanes() {
+        let mut h = SeaHasher::new();
+        assert_eq!(h.current_lane(), 0);
+        h.write(&[1; 7]);
+        assert_eq!(h.current_lane(), 0);
+        h.write(&[1; 2]);
+        assert_eq!(h.current_lane(), 1);
+        h.write(&[1; 23]);
+        assert_eq!(h.current_lane(), 0);
+    }
+
+    #[test]
+    fn block_aligned() {
+        let mut h = SeaHasher::new();
+        assert!(h.is_block_aligned());
+
+        for _ in 0..3 {
+            for n in [1, 7, 8, 9, 16, 24, 31].iter() {
+                let mut partial = h;
+                partial.write(&[1; 32][..*n]);
+                assert!(!partial.is_block_aligned());
+            }
+
+            h.write(&[1; 31]);
+            assert!(!h.is_block_aligned());
+            h.write_u8(1);
+            assert!(h.is_block_aligned());
+        }
+
+        h.write(&[2; 96]);
+        assert!(h.is_block_aligned());
+    }
+
+    #[test]
+    fn parts() {
+        let buf = b"love is a wonderful terrible thing, love is a wonderful terrible thing";
+
+        for checkpoint in (0..buf.len()).step_by(8) {
+            let mut h = SeaHasher::with_seed(42);
+            h.write(&buf[..checkpoint]);
+            let (lanes, cursor, total) = h.into_parts().unwrap();
+            assert_eq!(total, checkpoint as u64);
+
+            let mut resumed = SeaHasher::from_parts(lanes, cursor, total);
+            resumed.write(&buf[checkpoint..]);
+            h.write(&buf[checkpoint..]);
+            assert_eq!(resumed.finish(), h.finish());
+            assert_eq!(resumed.finish(), hash_seeded(buf, 42));
+        }
+
+        let mut h = SeaHasher::new();
+        h.write(&buf[..13]);
+        assert_eq!(h.into_parts(), None);
+        assert_eq!(SeaHasher::with_seed_and_salt(1, 2).into_parts(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn parts_unaligned() {
+        SeaHasher::from_parts([0; 4], 0, 13);
+    }
+
+    #[test]
+    fn finish_debug() {
+        let mut h = SeaHasher::new();
+        h.write(b"to be or not to be");
+
+        let (digest, lanes) = h.finish_debug();
+        assert_eq!(digest, h.finish());
+        assert_eq!(digest, diffuse(lanes[0] ^ lanes[1] ^ lanes[2] ^ lanes[3] ^ 18));
+
+        let mut h = SeaHasher::with_seed_and_salt(1, 2);
+        h.write(&[7; 64]);
+
+        let (digest, lanes) = h.finish_debug();
+        assert_eq!(digest, diffuse(lanes[0] ^ lanes[1] ^ lanes[2] ^ lanes[3] ^ 64 ^ 2));
+    }
+
+    #[test]
+    fn align_to_lane() {
+        let payload = b"to be or not to be";
+
+        // A record with a length field in lane 2 and the payload starting in lane 0.
+        let mut a = SeaHasher::new();
+        a.write(b"header");
+        a.align_to_lane(2);
+        a.write_u64(payload.len() as u64);
+        a.align_to_lane(0);
+        // Aligning when already aligned does nothing.
+        a.align_to_lane(0);
+        a.write(payload);
+
+        // The same record with explicit padding.
+        let mut b = SeaHasher::new();
+        b.write(b"header");
+        b.write(&[0; 2 + 8]);
+        b.write_u64(payload.len() as u64);
+        b.write(&[0; 8]);
+        b.write(payload);
+
+        assert_eq!(a.finish(), b.finish());
+        assert_eq!(a.to_raw(), b.to_raw());
+    }
+
+    #[test]
+    #[should_panic]
+    fn align_to_invalid_lane() {
+        SeaHasher::new().align_to_lane(4);
+    }
+
+    #[test]
+    fn unwrite() {
+        let mut h = SeaHasher::with_seed(42);
+        h.write(b"to be or not to be or not");
+        h.write(&[0; 7]);
+        let before = h.to_raw();
+
+        for &x in &[0, 1, 0xDEADBEEF, !0] {
+            h.write_u64(x);
+            h.unwrite_u64(x);
+            assert_eq!(h.to_raw(), before);
+        }
+
+        // Unwriting several words in a row, across the wrap-around of the cursor.
+        for x in 0..6 {
+            h.write_u64(x);
+        }
+        for x in (0..6).rev() {
+            h.unwrite_u64(x);
+        }
+        assert_eq!(h.to_raw(), before);
+
+        // Unwriting and then writing something else is like writing that in the first place.
+        let mut a = SeaHasher::with_seed(42);
+        a.write_u64(1);
+        a.unwrite_u64(1);
+        a.write_u64(2);
+        let mut b = SeaHasher::with_seed(42);
+        b.write_u64(2);
+        assert_eq!(a.finish(), b.finish());
+    }
+
+    #[test]
+    #[should_panic]
+    fn unwrite_unaligned() {
+        let mut h = SeaHasher::new();
+        h.write_u64(1);
+        h.write_u8(2);
+        h.unwrite_u64(1);
+    }
+
+    #[test]
+    fn raw() {
+        use core::mem;
+
+        assert_eq!(mem::size_of::<SeaHasherRaw>(), 64);
+        assert_eq!(mem::align_of::<SeaHasherRaw>(), 8);
+
+        let buf = b"love is a wonderful terrible thing";
+        for n in 0..buf.len() {
+            let mut h = SeaHasher::with_seed_and_salt(42, 7);
+            h.write(&buf[..n]);
+
+            let raw = h.to_raw();
+            assert_eq!(raw.buf_len as usize, n % 8);
+            assert_eq!(raw.cursor as usize, n / 8 % 4);
+            assert_eq!(raw.total, n as u64);
+            assert_eq!(&raw.buffered[..n % 8], &buf[n - n % 8..n]);
+
+            let mut restored = SeaHasher::from_raw(raw).unwrap();
+            assert_eq!(restored.finish(), h.finish());
+            assert_eq!(restored.to_raw(), raw);
+
+            restored.write(&buf[n..]);
+            h.write(&buf[n..]);
+            assert_eq!(restored.finish(), h.finish());
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        let prefix = b"love is a wonderful terrible thing, ";
+
+        let mut h = SeaHasher::with_seed(42);
+        h.write(prefix);
+        let snapshot = h.snapshot();
+
+        for suffix in [&b"the end"[..], b"to be or not to be"].iter() {
+            let mut restored = SeaHasher::from_snapshot(snapshot);
+            restored.write(suffix);
+
+            let mut full = SeaHasher::with_seed(42);
+            full.write(prefix);
+            full.write(suffix);
+
+            assert_eq!(restored.finish(), full.finish());
+        }
+    }
+
+    #[test]
+    fn raw_invalid() {
+        let raw = SeaHasher::new().to_raw();
+
+        assert!(SeaHasher::from_raw(SeaHasherRaw { cursor: 4, ..raw }).is_none());
+        assert!(SeaHasher::from_raw(SeaHasherRaw { buf_len: 8, ..raw }).is_none());
+
+        // Garbage past the buffered bytes is ignored.
+        let mut a = SeaHasher::new();
+        a.write(b"abc");
+        let mut raw = a.to_raw();
+        raw.buffered[5] = 0xFF;
+        assert_eq!(SeaHasher::from_raw(raw).unwrap().finish(), a.finish());
+    }
 }
@@ -0,0 +1,115 @@
+//! Hashing of structured values through `serde`.
+
+use std::io::{self, Write};
+use core::hash::Hasher;
+
+use bincode;
+use serde::Serialize;
+
+use SeaHasher;
+
+/// A writer feeding the written bytes into a hasher.
+struct HashWriter {
+    /// The hasher of the bytes written so far.
+    hasher: SeaHasher,
+}
+
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.write(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hash a serializable value according to a chosen seed.
+///
+/// The value is serialized with `bincode` (with its default, fixed-width little-endian encoding,
+/// and lengths as 64-bit integers), and the bytes are fed straight into a `SeaHasher`, so the
+/// serialized form is never buffered. The result is `hash_seeded()` of that encoding.
+///
+/// The encoding is canonical for a given type: equal values give equal digests, and it is
+/// independent of the platform. Fields are encoded in declaration order without their names, so
+/// renaming a field keeps the digest, while reordering fields changes it. Note that the
+/// serialization of e.g. `HashMap` follows its iteration order, which is not canonical, so use an
+/// ordered map (like `BTreeMap`) for the values to hash.
+///
+/// Errors of the serialization (e.g. a failing `Serialize` implementation) are returned as is.
+pub fn hash_serialize<T: Serialize + ?Sized>(value: &T, seed: u64) -> bincode::Result<u64> {
+    let mut writer = HashWriter {
+        hasher: SeaHasher::with_seed(seed),
+    };
+    bincode::serialize_into(&mut writer, value)?;
+
+    Ok(writer.hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use hash_seeded;
+
+    #[derive(Serialize)]
+    struct Config {
+        name: String,
+        threads: u32,
+        paths: Vec<String>,
+        limits: BTreeMap<String, u64>,
+    }
+
+    fn config() -> Config {
+        let mut limits = BTreeMap::new();
+        limits.insert("memory".to_string(), 1 << 30);
+        limits.insert("files".to_string(), 1024);
+
+        Config {
+            name: "cache".to_string(),
+            threads: 8,
+            paths: vec!["/tmp".to_string(), "/var/tmp".to_string()],
+            limits,
+        }
+    }
+
+    #[test]
+    fn equal() {
+        assert_eq!(hash_serialize(&config(), 42).unwrap(), hash_serialize(&config(), 42).unwrap());
+    }
+
+    #[test]
+    fn different() {
+        let a = hash_serialize(&config(), 42).unwrap();
+
+        let mut b = config();
+        b.threads = 9;
+        assert_ne!(hash_serialize(&b, 42).unwrap(), a);
+
+        let mut c = config();
+        c.paths.reverse();
+        assert_ne!(hash_serialize(&c, 42).unwrap(), a);
+
+        let mut d = config();
+        d.limits.insert("files".to_string(), 1025);
+        assert_ne!(hash_serialize(&d, 42).unwrap(), a);
+
+        assert_ne!(hash_serialize(&config(), 43).unwrap(), a);
+    }
+
+    #[test]
+    fn encoding() {
+        // `bincode` encodes tuples as their fields one after another, and strings with a 64-bit
+        // length prefix.
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&7u32.to_le_bytes());
+        bytes.extend_from_slice(&2u64.to_le_bytes());
+        bytes.extend_from_slice(b"hi");
+
+        assert_eq!(hash_serialize(&(7u32, "hi"), 42).unwrap(), hash_seeded(&bytes, 42));
+    }
+}
@@ -0,0 +1,120 @@
+//! Rolling digests of sliding windows.
+
+use diffuse;
+use consts::{INIT_B, P};
+
+/// The digest of a fixed-size window sliding over a stream of bytes.
+///
+/// `hash_seeded()` cannot be rolled (removing the first byte of the input would require redoing
+/// every lane), so this is a distinct construction, unrelated to `hash()` of the window. For a
+/// window `b[0], ..., b[w - 1]` (oldest first), every byte contributes `c(b) = diffuse(seed ^ k ^
+/// b)` (for a fixed constant `k`), and the state is the polynomial
+///
+/// ```text
+/// s = c(b[0]) pʷ⁻¹ + c(b[1]) pʷ⁻² + ... + c(b[w - 1])  (mod 2⁶⁴)
+/// ```
+///
+/// with the multiplier `p` of the diffusion function. The digest is `diffuse(s ⊕ w)`. As the
+/// contributions are added up, the outgoing byte can be removed by subtracting its term, and the
+/// window is shifted by multiplying with `p` (which is odd, and thus invertible), so `advance()`
+/// takes O(1), whatever the window size.
+///
+/// The digest is meant for content-defined chunking and the like, **not** for adversarial input:
+/// the state is linear in the contributions, so collisions can be constructed.
+#[derive(Clone, Copy, Debug)]
+pub struct WindowHash {
+    /// The seed of the contributions.
+    seed: u64,
+    /// The state, i.e. the polynomial of the contributions of the window.
+    state: u64,
+    /// The factor of the contribution of the oldest byte, `p^(w - 1)`.
+    oldest: u64,
+    /// The size of the window.
+    len: usize,
+}
+
+impl WindowHash {
+    /// Start rolling with some initial window, according to a chosen seed.
+    ///
+    /// The size of the window is fixed to the length of `window`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `window` is empty.
+    pub fn new(window: &[u8], seed: u64) -> WindowHash {
+        assert!(!window.is_empty(), "The window must not be empty.");
+
+        let mut hash = WindowHash {
+            seed,
+            state: 0,
+            oldest: 1,
+            len: window.len(),
+        };
+        hash.state = hash.contribution(window[0]);
+        for &byte in &window[1..] {
+            hash.state = hash.state.wrapping_mul(P).wrapping_add(hash.contribution(byte));
+            hash.oldest = hash.oldest.wrapping_mul(P);
+        }
+
+        hash
+    }
+
+    /// Slide the window by one byte.
+    ///
+    /// `old_byte` is the oldest byte of the window, which leaves it, and `new_byte` is the byte
+    /// entering it. If `old_byte` is not actually the oldest byte, the digest is meaningless.
+    pub fn advance(&mut self, old_byte: u8, new_byte: u8) {
+        let old = self.contribution(old_byte).wrapping_mul(self.oldest);
+        let new = self.contribution(new_byte);
+        self.state = self.state.wrapping_sub(old).wrapping_mul(P).wrapping_add(new);
+    }
+
+    /// Get the digest of the current window.
+    pub fn digest(&self) -> u64 {
+        diffuse(self.state ^ self.len as u64)
+    }
+
+    /// Get the contribution of some byte.
+    fn contribution(&self, byte: u8) -> u64 {
+        diffuse(self.seed ^ INIT_B ^ byte as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling() {
+        let mut buf = [0; 1000];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = diffuse(i as u64) as u8;
+        }
+
+        for &w in &[1, 7, 32, 37] {
+            let mut hash = WindowHash::new(&buf[..w], 42);
+            for i in 0..buf.len() - w {
+                hash.advance(buf[i], buf[i + w]);
+                assert_eq!(hash.digest(), WindowHash::new(&buf[i + 1..i + 1 + w], 42).digest());
+            }
+        }
+    }
+
+    #[test]
+    fn distinct() {
+        let buf = b"to be or not to be";
+
+        // The window size, the order of the bytes, and the seed all matter.
+        let digest = WindowHash::new(buf, 42).digest();
+        assert_ne!(WindowHash::new(&buf[1..], 42).digest(), digest);
+        assert_ne!(WindowHash::new(b"ot be or not to be", 42).digest(), digest);
+        assert_ne!(WindowHash::new(buf, 43).digest(), digest);
+        assert_ne!(WindowHash::new(&[0; 3], 0).digest(), WindowHash::new(&[0; 4], 0).digest());
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty() {
+        WindowHash::new(&[], 42);
+    }
+}